@@ -0,0 +1,98 @@
+//! The event types of `xai-solana-program`, in a crate of their own so that
+//! off-chain decoders can depend on them without building the program.
+
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct CreateTokenEvent {
+    pub identifier                : Pubkey,
+    pub user                      : Pubkey,
+    pub mint                      : Pubkey,
+    pub airdrop_amount            : u64,
+    pub max_supply                : u128,
+    pub total_supply              : u64,
+    pub init_virtual_sol_reserve  : u128,
+    pub init_virtual_token_reserve: u128,
+    pub sol_aim                   : u128,
+    pub dev_buy_amount_in         : u128,
+    pub dev_buy_amount_out        : u128,
+    pub trading_starts_at         : u64,
+    pub trading_start_is_slot     : bool,
+    pub allowlist_ends_at         : u64,
+}
+
+#[event]
+pub struct BuyEvent {
+    pub user                     : Pubkey,
+    pub user_token_account       : Pubkey,
+    pub mint                     : Pubkey,
+    pub virtual_sol_reserve      : u128,
+    pub virtual_token_reserve    : u128,
+    pub new_virtual_sol_reserve  : u128,
+    pub new_virtual_token_reserve: u128,
+    pub fee                      : u128,
+    /// Fee numerator applied, over `trade_fee_denominator`
+    pub fee_numerator            : u64,
+    pub integrator               : Option<Pubkey>,
+    pub integrator_fee           : u64,
+    pub amount_in                : u128,
+    pub amount_out               : u128,
+    pub timestamp                : u64,
+}
+
+#[event]
+pub struct SellEvent {
+    pub user                     : Pubkey,
+    pub user_token_account       : Pubkey,
+    pub mint                     : Pubkey,
+    pub virtual_sol_reserve      : u128,
+    pub virtual_token_reserve    : u128,
+    pub new_virtual_sol_reserve  : u128,
+    pub new_virtual_token_reserve: u128,
+    pub fee                      : u128,
+    /// Fee numerator applied, over `trade_fee_denominator`
+    pub fee_numerator            : u64,
+    pub integrator               : Option<Pubkey>,
+    pub integrator_fee           : u64,
+    pub amount_in                : u128,
+    pub amount_out               : u128,
+    pub timestamp                : u64,
+}
+
+#[event]
+pub struct TokenGraduatedEvent {
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct CreatorFeeEvent {
+    pub mint         : Pubkey,
+    pub creator      : Pubkey,
+    pub amount       : u64,
+    pub unclaimed_sol: u64,
+}
+
+#[event]
+pub struct ReferralRewardEvent {
+    pub user        : Pubkey,
+    pub mint        : Pubkey,
+    /// Recommenders paid, closest first
+    pub recommenders: Vec<Pubkey>,
+    pub rewards     : Vec<u64>,
+}
+
+#[event]
+pub struct ReferralTokenRewardEvent {
+    pub recommender              : Pubkey,
+    pub mint                     : Pubkey,
+    pub sol_amount               : u64,
+    pub token_amount             : u64,
+    pub new_virtual_sol_reserve  : u128,
+    pub new_virtual_token_reserve: u128,
+}
+
+#[event]
+pub struct RecommenderClaimSolEvent {
+    pub recommender : Pubkey,
+    pub claim_amount: u64,
+}
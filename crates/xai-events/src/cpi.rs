@@ -0,0 +1,213 @@
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use crate::{
+    raw, CreateTokenEvent, Error, RecommenderClaimSolEvent, ReferralTokenRewardEvent, TokenGraduated,
+    TradeEvent, XaiEvent,
+};
+
+/// Decode the data of a self-CPI event instruction, as found in the inner
+/// instructions of the program with the event authority as only account.
+/// Returns `None` for other instructions and for events not decoded here.
+pub fn decode_cpi_data(data: &[u8]) -> Result<Option<XaiEvent>, Error> {
+    let data = match data.strip_prefix(&EVENT_IX_TAG_LE[..]) {
+        Some(data) if data.len() >= 8 => data,
+        _ => return Ok(None),
+    };
+    let (discriminator, mut body) = data.split_at(8);
+
+    let event = if discriminator == raw::CreateTokenEvent::DISCRIMINATOR {
+        XaiEvent::CreateToken(deserialize::<raw::CreateTokenEvent>("CreateTokenEvent", &mut body)?.into())
+    } else if discriminator == raw::BuyEvent::DISCRIMINATOR {
+        XaiEvent::Buy(deserialize::<raw::BuyEvent>("BuyEvent", &mut body)?.into())
+    } else if discriminator == raw::SellEvent::DISCRIMINATOR {
+        XaiEvent::Sell(deserialize::<raw::SellEvent>("SellEvent", &mut body)?.into())
+    } else if discriminator == raw::TokenGraduatedEvent::DISCRIMINATOR {
+        XaiEvent::TokenGraduated(TokenGraduated {
+            mint: deserialize::<raw::TokenGraduatedEvent>("TokenGraduatedEvent", &mut body)?.mint,
+        })
+    } else if discriminator == raw::RecommenderClaimSolEvent::DISCRIMINATOR {
+        let event = deserialize::<raw::RecommenderClaimSolEvent>("RecommenderClaimSolEvent", &mut body)?;
+        XaiEvent::RecommenderClaimSol(RecommenderClaimSolEvent {
+            recommender : event.recommender,
+            claim_amount: event.claim_amount,
+        })
+    } else if discriminator == raw::ReferralTokenRewardEvent::DISCRIMINATOR {
+        let event = deserialize::<raw::ReferralTokenRewardEvent>("ReferralTokenRewardEvent", &mut body)?;
+        XaiEvent::ReferralTokenReward(ReferralTokenRewardEvent {
+            recommender              : event.recommender,
+            mint                     : event.mint,
+            sol_amount               : event.sol_amount,
+            token_amount             : event.token_amount,
+            new_virtual_sol_reserve  : event.new_virtual_sol_reserve,
+            new_virtual_token_reserve: event.new_virtual_token_reserve,
+        })
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(event))
+}
+
+/// `decode_cpi_data` for base58 instruction data, as returned by the RPC.
+pub fn decode_cpi_base58(data: &str) -> Result<Option<XaiEvent>, Error> {
+    decode_cpi_data(&bs58::decode(data).into_vec()?)
+}
+
+fn deserialize<T: AnchorDeserialize>(event: &'static str, body: &mut &[u8]) -> Result<T, Error> {
+    T::deserialize(body).map_err(|source| Error::Borsh { event, source })
+}
+
+impl From<raw::CreateTokenEvent> for CreateTokenEvent {
+    fn from(event: raw::CreateTokenEvent) -> Self {
+        CreateTokenEvent {
+            identifier                : event.identifier,
+            user                      : event.user,
+            mint                      : event.mint,
+            airdrop_amount            : event.airdrop_amount,
+            max_supply                : event.max_supply,
+            total_supply              : event.total_supply,
+            init_virtual_sol_reserve  : event.init_virtual_sol_reserve,
+            init_virtual_token_reserve: event.init_virtual_token_reserve,
+            sol_aim                   : Some(event.sol_aim),
+            dev_buy_amount_in         : event.dev_buy_amount_in,
+            dev_buy_amount_out        : event.dev_buy_amount_out,
+            trading_starts_at         : event.trading_starts_at,
+            trading_start_is_slot     : event.trading_start_is_slot,
+            allowlist_ends_at         : event.allowlist_ends_at,
+        }
+    }
+}
+
+macro_rules! impl_from_trade_event {
+    ($raw:ty) => {
+        impl From<$raw> for TradeEvent {
+            fn from(event: $raw) -> Self {
+                TradeEvent {
+                    user                     : event.user,
+                    user_token_account       : event.user_token_account,
+                    mint                     : event.mint,
+                    virtual_sol_reserve      : event.virtual_sol_reserve,
+                    virtual_token_reserve    : event.virtual_token_reserve,
+                    new_virtual_sol_reserve  : event.new_virtual_sol_reserve,
+                    new_virtual_token_reserve: event.new_virtual_token_reserve,
+                    fee                      : event.fee,
+                    fee_numerator            : Some(event.fee_numerator),
+                    integrator               : event.integrator,
+                    integrator_fee           : event.integrator_fee,
+                    amount_in                : event.amount_in,
+                    amount_out               : event.amount_out,
+                    timestamp                : event.timestamp,
+                }
+            }
+        }
+    };
+}
+
+impl_from_trade_event!(raw::BuyEvent);
+impl_from_trade_event!(raw::SellEvent);
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Event;
+
+    use super::*;
+    use crate::Pubkey;
+
+    fn payload<E: Event>(event: &E) -> Vec<u8> {
+        EVENT_IX_TAG_LE.into_iter().chain(event.data()).collect()
+    }
+
+    fn sell() -> raw::SellEvent {
+        raw::SellEvent {
+            user                     : Pubkey::new_unique(),
+            user_token_account       : Pubkey::new_unique(),
+            mint                     : Pubkey::new_unique(),
+            virtual_sol_reserve      : 31_000_000_000,
+            virtual_token_reserve    : 1_038_387_096_774_193_548,
+            new_virtual_sol_reserve  : 30_000_000_000,
+            new_virtual_token_reserve: 1_073_000_000_000_000_000,
+            fee                      : 10_000_000,
+            fee_numerator            : 100,
+            integrator               : Some(Pubkey::new_unique()),
+            integrator_fee           : 1_000_000,
+            amount_in                : 34_612_903_225_806_452,
+            amount_out               : 990_000_000,
+            timestamp                : 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn decodes_sell_payload() {
+        let sell = sell();
+        let event = decode_cpi_base58(&bs58::encode(payload(&sell)).into_string()).unwrap().unwrap();
+
+        let XaiEvent::Sell(decoded) = event else { panic!("not a sell: {event:?}") };
+        assert_eq!(decoded, TradeEvent::from(sell));
+        assert_eq!(decoded.fee_numerator, Some(100));
+    }
+
+    #[test]
+    fn decodes_create_token_payload() {
+        let create = raw::CreateTokenEvent {
+            identifier                : Pubkey::new_unique(),
+            user                      : Pubkey::new_unique(),
+            mint                      : Pubkey::new_unique(),
+            airdrop_amount            : 0,
+            max_supply                : 1_000_000_000_000_000_000,
+            total_supply              : 1_000_000_000_000_000_000,
+            init_virtual_sol_reserve  : 30_000_000_000,
+            init_virtual_token_reserve: 1_073_000_000_000_000_000,
+            sol_aim                   : 85_000_000_000,
+            dev_buy_amount_in         : 0,
+            dev_buy_amount_out        : 0,
+            trading_starts_at         : 0,
+            trading_start_is_slot     : false,
+            allowlist_ends_at         : 0,
+        };
+        let event = decode_cpi_data(&payload(&create)).unwrap().unwrap();
+
+        let XaiEvent::CreateToken(decoded) = event else { panic!("not a create: {event:?}") };
+        assert_eq!(decoded.sol_aim, Some(85_000_000_000));
+        assert_eq!(decoded.mint, create.mint);
+    }
+
+    #[test]
+    fn decodes_referral_token_reward_payload() {
+        let reward = raw::ReferralTokenRewardEvent {
+            recommender              : Pubkey::new_unique(),
+            mint                     : Pubkey::new_unique(),
+            sol_amount               : 2_000_000,
+            token_amount             : 69_000_000_000_000,
+            new_virtual_sol_reserve  : 31_002_000_000,
+            new_virtual_token_reserve: 1_038_318_000_000_000_000,
+        };
+        let event = decode_cpi_data(&payload(&reward)).unwrap().unwrap();
+
+        let XaiEvent::ReferralTokenReward(decoded) = event else { panic!("not a reward: {event:?}") };
+        assert_eq!(decoded.recommender, reward.recommender);
+        assert_eq!(decoded.new_virtual_token_reserve, 1_038_318_000_000_000_000);
+    }
+
+    #[test]
+    fn skips_other_instructions_and_events() {
+        // an instruction of the program that is not an event
+        assert!(decode_cpi_data(&[102, 6, 61, 18, 1, 218, 235, 234]).unwrap().is_none());
+
+        let reward = raw::ReferralRewardEvent {
+            user        : Pubkey::new_unique(),
+            mint        : Pubkey::new_unique(),
+            recommenders: vec![Pubkey::new_unique()],
+            rewards     : vec![1],
+        };
+        assert!(decode_cpi_data(&payload(&reward)).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_truncated_payload() {
+        let mut data = payload(&sell());
+        data.truncate(data.len() - 1);
+
+        assert!(matches!(decode_cpi_data(&data), Err(Error::Borsh { event: "SellEvent", .. })));
+        assert!(matches!(decode_cpi_base58("0OIl"), Err(Error::Base58(_))));
+    }
+}
@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid {event} json: {source}")]
+    Json {
+        event : &'static str,
+        source: serde_json::Error,
+    },
+    #[error("invalid {event} data: {source}")]
+    Borsh {
+        event : &'static str,
+        source: std::io::Error,
+    },
+    #[error("invalid pubkey {0}")]
+    Pubkey(String),
+    #[error("invalid base58 instruction data: {0}")]
+    Base58(#[from] bs58::decode::Error),
+}
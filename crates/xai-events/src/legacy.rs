@@ -0,0 +1,328 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::{
+    CreateTokenEvent, Error, Pubkey, ReferralTokenRewardEvent, TokenGraduated, TradeEvent, XaiEvent, PROGRAM_ID,
+};
+
+const LOG_PREFIX: &str = "Program log: ";
+const PROGRAM_PREFIX: &str = "Program ";
+
+/// `$CreateTokenEvent` line, fields added to the program later default to zero.
+#[derive(Debug, Deserialize)]
+struct LegacyCreateToken {
+    id  : String,   // identifier
+    u   : String,   // user
+    mint: String,   // token_mint
+    aa  : u64,      // airdrop_amount
+    ms  : u128,     // max_supply
+    ts  : u64,      // total_supply
+    rs  : u128,     // init_virtual_sol_reserve
+    rt  : u128,     // init_virtual_token_reserve
+    #[serde(default)]
+    db  : u128,     // dev_buy_amount_in
+    #[serde(default)]
+    dbo : u128,     // dev_buy_amount_out
+    #[serde(default)]
+    sa  : u64,      // trading_starts_at
+    #[serde(default)]
+    ss  : bool,     // trading_start_is_slot
+    #[serde(default)]
+    ae  : u64,      // allowlist_ends_at
+}
+
+/// `$BuyEvent` and `$SellEvent` lines.
+#[derive(Debug, Deserialize)]
+struct LegacyTrade {
+    u   : String,   // user
+    ua  : String,   // user's token account
+    mint: String,   // token_mint
+    vsr : u128,     // virtual_sol_reserve
+    vtr : u128,     // virtual_token_reserve
+    nvsr: u128,     // new_virtual_sol_reserve
+    nvtr: u128,     // new_virtual_token_reserve
+    f   : u128,     // fee
+    #[serde(default)]
+    fr  : Option<u64>,      // fee numerator
+    #[serde(default)]
+    ig  : Option<String>,   // integrator
+    #[serde(default)]
+    igf : u64,      // integrator fee
+    i   : u128,     // amount_in
+    o   : u128,     // amount_out
+    t   : u64,      // timestamp
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyTokenGraduated {
+    mint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyReferralTokenReward {
+    r   : String,   // recommender
+    mint: String,   // token_mint
+    s   : u64,      // SOL reward spent
+    o   : u64,      // tokens received
+    nvsr: u128,     // new_virtual_sol_reserve
+    nvtr: u128,     // new_virtual_token_reserve
+}
+
+/// Decode the events of a transaction's log messages, in order. Only lines
+/// logged while the program is the innermost one running are read, any other
+/// program can log the same `$NameEvent: {..}` text.
+pub fn decode_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<XaiEvent>, Error> {
+    let program_id = PROGRAM_ID.to_string();
+    let mut invoked: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+        match program_log(line) {
+            Some((program, ProgramLog::Invoke)) => invoked.push(program),
+            Some((_, ProgramLog::Exit)) => {
+                invoked.pop();
+            }
+            _ if invoked.last() == Some(&program_id.as_str()) => {
+                if let Some(event) = decode_log_line(line)? {
+                    events.push(event);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(events)
+}
+
+enum ProgramLog {
+    Invoke,
+    Exit,
+}
+
+/// `Program <id> invoke [n]`, `Program <id> success` and `Program <id> failed: ..`
+fn program_log(line: &str) -> Option<(&str, ProgramLog)> {
+    let (program, rest) = line.strip_prefix(PROGRAM_PREFIX)?.split_once(' ')?;
+    if rest.starts_with("invoke [") {
+        Some((program, ProgramLog::Invoke))
+    } else if rest == "success" || rest.starts_with("failed") {
+        Some((program, ProgramLog::Exit))
+    } else {
+        None
+    }
+}
+
+/// Decode one log message, with or without the `Program log: ` prefix.
+/// Returns `None` for lines that are not legacy events. Use `decode_logs` for
+/// the logs of a transaction, it skips lines of other programs.
+pub fn decode_log_line(line: &str) -> Result<Option<XaiEvent>, Error> {
+    let line = line.strip_prefix(LOG_PREFIX).unwrap_or(line);
+    let (name, json) = match line.strip_prefix('$').and_then(|line| line.split_once(": ")) {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+
+    let event = match name {
+        "CreateTokenEvent" => XaiEvent::CreateToken(
+            parse::<LegacyCreateToken>("CreateTokenEvent", json)?.try_into()?
+        ),
+        "BuyEvent" => XaiEvent::Buy(parse::<LegacyTrade>("BuyEvent", json)?.try_into()?),
+        "SellEvent" => XaiEvent::Sell(parse::<LegacyTrade>("SellEvent", json)?.try_into()?),
+        "TokenGraduatedEvent" => XaiEvent::TokenGraduated(TokenGraduated {
+            mint: pubkey(&parse::<LegacyTokenGraduated>("TokenGraduatedEvent", json)?.mint)?,
+        }),
+        "ReferralTokenRewardEvent" => XaiEvent::ReferralTokenReward(
+            parse::<LegacyReferralTokenReward>("ReferralTokenRewardEvent", json)?.try_into()?
+        ),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(event))
+}
+
+// u128 fields are read straight from the JSON text, never through an f64
+fn parse<'a, T: Deserialize<'a>>(event: &'static str, json: &'a str) -> Result<T, Error> {
+    serde_json::from_str(json).map_err(|source| Error::Json { event, source })
+}
+
+fn pubkey(s: &str) -> Result<Pubkey, Error> {
+    Pubkey::from_str(s).map_err(|_| Error::Pubkey(s.to_string()))
+}
+
+impl TryFrom<LegacyCreateToken> for CreateTokenEvent {
+    type Error = Error;
+
+    fn try_from(event: LegacyCreateToken) -> Result<Self, Error> {
+        Ok(CreateTokenEvent {
+            identifier                : pubkey(&event.id)?,
+            user                      : pubkey(&event.u)?,
+            mint                      : pubkey(&event.mint)?,
+            airdrop_amount            : event.aa,
+            max_supply                : event.ms,
+            total_supply              : event.ts,
+            init_virtual_sol_reserve  : event.rs,
+            init_virtual_token_reserve: event.rt,
+            sol_aim                   : None,
+            dev_buy_amount_in         : event.db,
+            dev_buy_amount_out        : event.dbo,
+            trading_starts_at         : event.sa,
+            trading_start_is_slot     : event.ss,
+            allowlist_ends_at         : event.ae,
+        })
+    }
+}
+
+impl TryFrom<LegacyTrade> for TradeEvent {
+    type Error = Error;
+
+    fn try_from(event: LegacyTrade) -> Result<Self, Error> {
+        Ok(TradeEvent {
+            user                     : pubkey(&event.u)?,
+            user_token_account       : pubkey(&event.ua)?,
+            mint                     : pubkey(&event.mint)?,
+            virtual_sol_reserve      : event.vsr,
+            virtual_token_reserve    : event.vtr,
+            new_virtual_sol_reserve  : event.nvsr,
+            new_virtual_token_reserve: event.nvtr,
+            fee                      : event.f,
+            fee_numerator            : event.fr,
+            integrator               : event.ig.as_deref().map(pubkey).transpose()?,
+            integrator_fee           : event.igf,
+            amount_in                : event.i,
+            amount_out               : event.o,
+            timestamp                : event.t,
+        })
+    }
+}
+
+impl TryFrom<LegacyReferralTokenReward> for ReferralTokenRewardEvent {
+    type Error = Error;
+
+    fn try_from(event: LegacyReferralTokenReward) -> Result<Self, Error> {
+        Ok(ReferralTokenRewardEvent {
+            recommender              : pubkey(&event.r)?,
+            mint                     : pubkey(&event.mint)?,
+            sol_amount               : event.s,
+            token_amount             : event.o,
+            new_virtual_sol_reserve  : event.nvsr,
+            new_virtual_token_reserve: event.nvtr,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const MINT: &str = "So11111111111111111111111111111111111111112";
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+    fn buy_line() -> String {
+        format!(
+            "Program log: $BuyEvent: {{\"u\":\"{USER}\",\"ua\":\"{USER}\",\"mint\":\"{MINT}\",\
+             \"vsr\":30000000000,\"vtr\":1073000000000000000,\"nvsr\":31000000000,\
+             \"nvtr\":1038387096774193548,\"f\":10000000,\"i\":1010000000,\
+             \"o\":34612903225806452,\"t\":1700000000}}"
+        )
+    }
+
+    fn invoke(program: &str, depth: u8) -> String {
+        format!("Program {program} invoke [{depth}]")
+    }
+
+    fn success(program: &str) -> String {
+        format!("Program {program} success")
+    }
+
+    #[test]
+    fn decodes_buy_line() {
+        let event = decode_log_line(&buy_line()).unwrap().unwrap();
+
+        let XaiEvent::Buy(buy) = event else { panic!("not a buy: {event:?}") };
+        assert_eq!(buy.user, pubkey(USER).unwrap());
+        assert_eq!(buy.mint, pubkey(MINT).unwrap());
+        assert_eq!(buy.virtual_token_reserve, 1_073_000_000_000_000_000);
+        assert_eq!(buy.amount_out, 34_612_903_225_806_452);
+        // older lines have no fee numerator or integrator
+        assert_eq!(buy.fee_numerator, None);
+        assert_eq!(buy.integrator, None);
+        assert_eq!(buy.integrator_fee, 0);
+    }
+
+    #[test]
+    fn decodes_create_token_line_without_later_fields() {
+        let line = format!(
+            "$CreateTokenEvent: {{\"id\":\"{USER}\",\"u\":\"{USER}\",\"mint\":\"{MINT}\",\
+             \"aa\":0,\"ms\":1000000000000000000,\"ts\":1000000000000000000,\
+             \"rs\":30000000000,\"rt\":1073000000000000000}}"
+        );
+        let event = decode_log_line(&line).unwrap().unwrap();
+
+        let XaiEvent::CreateToken(create) = event else { panic!("not a create: {event:?}") };
+        assert_eq!(create.max_supply, 1_000_000_000_000_000_000);
+        assert_eq!(create.sol_aim, None);
+        assert_eq!(create.dev_buy_amount_in, 0);
+    }
+
+    #[test]
+    fn decodes_referral_token_reward_line() {
+        let line = format!(
+            "Program log: $ReferralTokenRewardEvent: {{\"r\":\"{USER}\",\"mint\":\"{MINT}\",\
+             \"s\":2000000,\"o\":69000000000000,\"nvsr\":31002000000,\"nvtr\":1038318000000000000}}"
+        );
+        let event = decode_log_line(&line).unwrap().unwrap();
+
+        let XaiEvent::ReferralTokenReward(reward) = event else { panic!("not a reward: {event:?}") };
+        assert_eq!(reward.recommender, pubkey(USER).unwrap());
+        assert_eq!(reward.token_amount, 69_000_000_000_000);
+        assert_eq!(reward.new_virtual_sol_reserve, 31_002_000_000);
+    }
+
+    #[test]
+    fn skips_other_lines() {
+        assert!(decode_log_line("Program log: Instruction: Buy").unwrap().is_none());
+        assert!(decode_log_line("Program log: $UnknownEvent: {}").unwrap().is_none());
+        assert!(decode_log_line("Program log: $BuyEvent: {").is_err());
+    }
+
+    #[test]
+    fn decodes_lines_of_the_program_only() {
+        let program = PROGRAM_ID.to_string();
+        let logs = vec![
+            // another program logging the same text
+            invoke(TOKEN_PROGRAM, 1),
+            buy_line(),
+            success(TOKEN_PROGRAM),
+            invoke(&program, 1),
+            "Program log: Instruction: Buy".to_string(),
+            // a program called by this one
+            invoke(TOKEN_PROGRAM, 2),
+            buy_line(),
+            success(TOKEN_PROGRAM),
+            buy_line(),
+            format!("Program {program} consumed 52000 of 200000 compute units"),
+            success(&program),
+            buy_line(),
+        ];
+
+        let events = decode_logs(&logs).unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], XaiEvent::Buy(_)));
+    }
+
+    #[test]
+    fn failed_inner_program_pops() {
+        let program = PROGRAM_ID.to_string();
+        let logs = vec![
+            invoke(&program, 1),
+            invoke(TOKEN_PROGRAM, 2),
+            format!("Program {TOKEN_PROGRAM} failed: custom program error: 0x1"),
+            buy_line(),
+            success(&program),
+        ];
+
+        assert_eq!(decode_logs(&logs).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+//! Decoder for the events of `xai-solana-program`, from either the legacy
+//! `$NameEvent: {..}` JSON log lines or the Anchor self-CPI event instructions.
+
+pub mod cpi;
+pub mod error;
+pub mod legacy;
+
+/// The program's event types, shared through `xai-event-types` so that this
+/// crate does not build the program and its dependencies.
+pub use xai_event_types as raw;
+
+pub use anchor_lang::prelude::Pubkey;
+pub use cpi::*;
+pub use error::*;
+pub use legacy::*;
+
+/// `declare_id!` of `xai-solana-program`
+pub const PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("65tLehMbGRJUYJDNP5V2nCy3oVRBQW315gtLxuCSJ88b");
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum XaiEvent {
+    CreateToken(CreateTokenEvent),
+    Buy(BuyEvent),
+    Sell(SellEvent),
+    TokenGraduated(TokenGraduated),
+    RecommenderClaimSol(RecommenderClaimSolEvent),
+    ReferralTokenReward(ReferralTokenRewardEvent),
+}
+
+impl XaiEvent {
+    pub fn mint(&self) -> Option<Pubkey> {
+        match self {
+            XaiEvent::CreateToken(event)         => Some(event.mint),
+            XaiEvent::Buy(event)                 => Some(event.mint),
+            XaiEvent::Sell(event)                => Some(event.mint),
+            XaiEvent::TokenGraduated(event)      => Some(event.mint),
+            XaiEvent::RecommenderClaimSol(_)     => None,
+            XaiEvent::ReferralTokenReward(event) => Some(event.mint),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateTokenEvent {
+    pub identifier                : Pubkey,
+    pub user                      : Pubkey,
+    pub mint                      : Pubkey,
+    pub airdrop_amount            : u64,
+    pub max_supply                : u128,
+    pub total_supply              : u64,
+    pub init_virtual_sol_reserve  : u128,
+    pub init_virtual_token_reserve: u128,
+    /// Not in the legacy log line
+    pub sol_aim                   : Option<u128>,
+    pub dev_buy_amount_in         : u128,
+    pub dev_buy_amount_out        : u128,
+    pub trading_starts_at         : u64,
+    pub trading_start_is_slot     : bool,
+    pub allowlist_ends_at         : u64,
+}
+
+/// A buy or a sell, `amount_in` is SOL for a buy and tokens for a sell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeEvent {
+    pub user                     : Pubkey,
+    pub user_token_account       : Pubkey,
+    pub mint                     : Pubkey,
+    pub virtual_sol_reserve      : u128,
+    pub virtual_token_reserve    : u128,
+    pub new_virtual_sol_reserve  : u128,
+    pub new_virtual_token_reserve: u128,
+    pub fee                      : u128,
+    /// Over `FeeConfig.trade_fee_denominator`, missing from older log lines
+    pub fee_numerator            : Option<u64>,
+    pub integrator               : Option<Pubkey>,
+    pub integrator_fee           : u64,
+    pub amount_in                : u128,
+    pub amount_out               : u128,
+    pub timestamp                : u64,
+}
+
+pub type BuyEvent = TradeEvent;
+pub type SellEvent = TradeEvent;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenGraduated {
+    pub mint: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecommenderClaimSolEvent {
+    pub recommender : Pubkey,
+    pub claim_amount: u64,
+}
+
+/// A referral reward bought on the curve after a trade, it moves the curve's reserves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferralTokenRewardEvent {
+    pub recommender              : Pubkey,
+    pub mint                     : Pubkey,
+    pub sol_amount               : u64,
+    pub token_amount             : u64,
+    pub new_virtual_sol_reserve  : u128,
+    pub new_virtual_token_reserve: u128,
+}
@@ -0,0 +1,493 @@
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use xai_events::{CreateTokenEvent, TradeEvent, XaiEvent};
+
+use crate::transaction::ProgramTransaction;
+
+/// Candle intervals, name and length in seconds.
+pub const CANDLE_INTERVALS: [(&str, i64); 3] = [("1m", 60), ("5m", 300), ("1h", 3600)];
+
+// u128 amounts are stored as TEXT to keep every digit, prices and volumes as REAL.
+// `sol_amount` is gross of the trading fee on both sides. Candles keep the
+// (slot, event_index) of their open and close trades and curves that of their
+// latest reserves, dumps may not be in order.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trades (
+    signature             TEXT    NOT NULL,
+    event_index           INTEGER NOT NULL,
+    slot                  INTEGER NOT NULL,
+    mint                  TEXT    NOT NULL,
+    side                  TEXT    NOT NULL,
+    user                  TEXT    NOT NULL,
+    sol_amount            TEXT    NOT NULL,
+    token_amount          TEXT    NOT NULL,
+    fee                   TEXT    NOT NULL,
+    virtual_sol_reserve   TEXT    NOT NULL,
+    virtual_token_reserve TEXT    NOT NULL,
+    price                 REAL    NOT NULL,
+    timestamp             INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS trades_mint_timestamp ON trades (mint, timestamp);
+
+CREATE TABLE IF NOT EXISTS candles (
+    mint         TEXT    NOT NULL,
+    interval     TEXT    NOT NULL,
+    open_time    INTEGER NOT NULL,
+    open         REAL    NOT NULL,
+    high         REAL    NOT NULL,
+    low          REAL    NOT NULL,
+    close        REAL    NOT NULL,
+    open_slot    INTEGER NOT NULL,
+    open_index   INTEGER NOT NULL,
+    close_slot   INTEGER NOT NULL,
+    close_index  INTEGER NOT NULL,
+    volume_sol   REAL    NOT NULL,
+    volume_token REAL    NOT NULL,
+    trades       INTEGER NOT NULL,
+    PRIMARY KEY (mint, interval, open_time)
+);
+
+CREATE TABLE IF NOT EXISTS curves (
+    mint                     TEXT PRIMARY KEY,
+    creator                  TEXT,
+    created_at               INTEGER,
+    init_virtual_sol_reserve TEXT,
+    sol_aim                  TEXT,
+    virtual_sol_reserve      TEXT,
+    virtual_token_reserve    TEXT,
+    reserves_slot            INTEGER,
+    reserves_index           INTEGER,
+    progress                 REAL,
+    graduated_at             INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS holders (
+    mint    TEXT    NOT NULL,
+    owner   TEXT    NOT NULL,
+    balance INTEGER NOT NULL,
+    PRIMARY KEY (mint, owner)
+);
+
+CREATE TABLE IF NOT EXISTS cursor (
+    id        INTEGER PRIMARY KEY CHECK (id = 0),
+    signature TEXT    NOT NULL
+);
+";
+
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        // databases created when reserves were ordered by `updated_at`
+        let ordered_by_slot: bool = conn.query_row(
+            "SELECT count(*) > 0 FROM pragma_table_info('curves') WHERE name = 'reserves_slot'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !ordered_by_slot {
+            conn.execute_batch(
+                "ALTER TABLE curves ADD COLUMN reserves_slot INTEGER;
+                 ALTER TABLE curves ADD COLUMN reserves_index INTEGER;",
+            )?;
+        }
+
+        Ok(Db { conn })
+    }
+
+    /// Newest signature fetched from the RPC.
+    pub fn cursor(&self) -> Result<Option<String>> {
+        Ok(self.conn
+            .query_row("SELECT signature FROM cursor WHERE id = 0", [], |row| row.get(0))
+            .optional()?)
+    }
+
+    pub fn set_cursor(&self, signature: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO cursor (id, signature) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET signature = excluded.signature",
+            params![signature],
+        )?;
+
+        Ok(())
+    }
+
+    /// Index the events of `transaction`, returns the number of new trades.
+    /// Trades already indexed are skipped, so dumps can be replayed.
+    pub fn index(&mut self, transaction: &ProgramTransaction) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut new_trades = 0;
+        let mut last_timestamp = None;
+
+        for (event_index, event) in transaction.events.iter().enumerate() {
+            match event {
+                XaiEvent::Buy(trade) | XaiEvent::Sell(trade) => {
+                    let is_buy = matches!(event, XaiEvent::Buy(_));
+                    last_timestamp = Some(trade.timestamp as i64);
+                    if insert_trade(&tx, transaction, event_index, trade, is_buy)? {
+                        new_trades += 1;
+                    }
+                }
+                XaiEvent::CreateToken(create) => {
+                    insert_curve(&tx, create, transaction.block_time)?;
+                }
+                XaiEvent::TokenGraduated(graduated) => {
+                    tx.execute(
+                        "INSERT INTO curves (mint, progress, graduated_at) VALUES (?1, 1.0, ?2)
+                         ON CONFLICT (mint) DO UPDATE SET progress = 1.0, graduated_at = excluded.graduated_at",
+                        params![graduated.mint.to_string(), transaction.block_time.or(last_timestamp)],
+                    )?;
+                }
+                // a referral reward bought on the curve right after the trade
+                XaiEvent::ReferralTokenReward(reward) => {
+                    let mint = reward.mint.to_string();
+                    update_reserves(
+                        &tx,
+                        &mint,
+                        reward.new_virtual_sol_reserve,
+                        reward.new_virtual_token_reserve,
+                        transaction.slot,
+                        event_index,
+                    )?;
+                    update_progress(&tx, &mint)?;
+                }
+                XaiEvent::RecommenderClaimSol(_) => {}
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(new_trades)
+    }
+}
+
+/// Insert the trade and fold it into the candles, curve and holders.
+/// Returns false when the trade was already indexed.
+fn insert_trade(
+    tx         : &Transaction,
+    transaction: &ProgramTransaction,
+    event_index: usize,
+    trade      : &TradeEvent,
+    is_buy     : bool,
+) -> Result<bool> {
+    let mint = trade.mint.to_string();
+    let (side, sol_amount, token_amount) = if is_buy {
+        ("buy", trade.amount_in, trade.amount_out)
+    } else {
+        ("sell", trade.amount_out + trade.fee, trade.amount_in)
+    };
+    // lamports per token base unit
+    let price = trade.new_virtual_sol_reserve as f64 / trade.new_virtual_token_reserve as f64;
+    let timestamp = trade.timestamp as i64;
+
+    let inserted = tx.execute(
+        "INSERT OR IGNORE INTO trades (
+            signature, event_index, slot, mint, side, user, sol_amount, token_amount, fee,
+            virtual_sol_reserve, virtual_token_reserve, price, timestamp
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            transaction.signature,
+            event_index,
+            transaction.slot,
+            mint,
+            side,
+            trade.user.to_string(),
+            sol_amount.to_string(),
+            token_amount.to_string(),
+            trade.fee.to_string(),
+            trade.new_virtual_sol_reserve.to_string(),
+            trade.new_virtual_token_reserve.to_string(),
+            price,
+            timestamp,
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(false);
+    }
+
+    for (interval, seconds) in CANDLE_INTERVALS {
+        tx.execute(
+            "INSERT INTO candles (
+                mint, interval, open_time, open, high, low, close,
+                open_slot, open_index, close_slot, close_index, volume_sol, volume_token, trades
+            ) VALUES (?1, ?2, ?3, ?4, ?4, ?4, ?4, ?5, ?6, ?5, ?6, ?7, ?8, 1)
+            ON CONFLICT (mint, interval, open_time) DO UPDATE SET
+                open         = CASE WHEN (excluded.open_slot, excluded.open_index) < (open_slot, open_index)
+                                    THEN excluded.open ELSE open END,
+                open_slot    = CASE WHEN (excluded.open_slot, excluded.open_index) < (open_slot, open_index)
+                                    THEN excluded.open_slot ELSE open_slot END,
+                open_index   = CASE WHEN (excluded.open_slot, excluded.open_index) < (open_slot, open_index)
+                                    THEN excluded.open_index ELSE open_index END,
+                close        = CASE WHEN (excluded.close_slot, excluded.close_index) > (close_slot, close_index)
+                                    THEN excluded.close ELSE close END,
+                close_slot   = CASE WHEN (excluded.close_slot, excluded.close_index) > (close_slot, close_index)
+                                    THEN excluded.close_slot ELSE close_slot END,
+                close_index  = CASE WHEN (excluded.close_slot, excluded.close_index) > (close_slot, close_index)
+                                    THEN excluded.close_index ELSE close_index END,
+                high         = max(high, excluded.high),
+                low          = min(low, excluded.low),
+                volume_sol   = volume_sol + excluded.volume_sol,
+                volume_token = volume_token + excluded.volume_token,
+                trades       = trades + 1",
+            params![
+                mint,
+                interval,
+                timestamp - timestamp.rem_euclid(seconds),
+                price,
+                transaction.slot,
+                event_index,
+                sol_amount as f64,
+                token_amount as f64,
+            ],
+        )?;
+    }
+
+    update_reserves(
+        tx,
+        &mint,
+        trade.new_virtual_sol_reserve,
+        trade.new_virtual_token_reserve,
+        transaction.slot,
+        event_index,
+    )?;
+    update_progress(tx, &mint)?;
+
+    // net of curve trades, transfers and Token-2022 transfer fees are not seen here
+    let delta = i64::try_from(token_amount)?;
+    tx.execute(
+        "INSERT INTO holders (mint, owner, balance) VALUES (?1, ?2, ?3)
+         ON CONFLICT (mint, owner) DO UPDATE SET balance = balance + excluded.balance",
+        params![mint, trade.user.to_string(), if is_buy { delta } else { -delta }],
+    )?;
+
+    Ok(true)
+}
+
+/// Reserves only move forward in (slot, event_index), dumps may not be in
+/// order and trades of a slot share its timestamp.
+fn update_reserves(
+    tx                   : &Transaction,
+    mint                 : &str,
+    virtual_sol_reserve  : u128,
+    virtual_token_reserve: u128,
+    slot                 : u64,
+    event_index          : usize,
+) -> Result<()> {
+    tx.execute(
+        "INSERT INTO curves (mint, virtual_sol_reserve, virtual_token_reserve, reserves_slot, reserves_index)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (mint) DO UPDATE SET
+            virtual_sol_reserve   = excluded.virtual_sol_reserve,
+            virtual_token_reserve = excluded.virtual_token_reserve,
+            reserves_slot         = excluded.reserves_slot,
+            reserves_index        = excluded.reserves_index
+         WHERE curves.reserves_slot IS NULL
+            OR (curves.reserves_slot, curves.reserves_index) < (excluded.reserves_slot, excluded.reserves_index)",
+        params![
+            mint,
+            virtual_sol_reserve.to_string(),
+            virtual_token_reserve.to_string(),
+            slot,
+            event_index,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn insert_curve(tx: &Transaction, create: &CreateTokenEvent, block_time: Option<i64>) -> Result<()> {
+    let mint = create.mint.to_string();
+    tx.execute(
+        "INSERT INTO curves (mint, creator, created_at, init_virtual_sol_reserve, sol_aim)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (mint) DO UPDATE SET
+            creator                  = excluded.creator,
+            created_at               = excluded.created_at,
+            init_virtual_sol_reserve = excluded.init_virtual_sol_reserve,
+            sol_aim                  = excluded.sol_aim",
+        params![
+            mint,
+            create.user.to_string(),
+            block_time,
+            create.init_virtual_sol_reserve.to_string(),
+            create.sol_aim.map(|sol_aim| sol_aim.to_string()),
+        ],
+    )?;
+
+    update_progress(tx, &mint)
+}
+
+/// SOL raised on the curve over the `sol_aim` at launch. Unknown for curves
+/// whose `CreateTokenEvent` was not indexed or came from a legacy log line.
+fn update_progress(tx: &Transaction, mint: &str) -> Result<()> {
+    let (init_virtual_sol_reserve, sol_aim, virtual_sol_reserve, graduated_at): (
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<i64>,
+    ) = tx.query_row(
+        "SELECT init_virtual_sol_reserve, sol_aim, virtual_sol_reserve, graduated_at
+         FROM curves WHERE mint = ?1",
+        params![mint],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+
+    let progress = match (init_virtual_sol_reserve, sol_aim, virtual_sol_reserve) {
+        _ if graduated_at.is_some() => Some(1.0),
+        (Some(init_virtual_sol_reserve), Some(sol_aim), Some(virtual_sol_reserve)) => {
+            let raised = virtual_sol_reserve.parse::<u128>()?
+                .saturating_sub(init_virtual_sol_reserve.parse::<u128>()?);
+            let sol_aim = sol_aim.parse::<u128>()?;
+            (sol_aim > 0).then(|| (raised as f64 / sol_aim as f64).min(1.0))
+        }
+        _ => None,
+    };
+
+    tx.execute(
+        "UPDATE curves SET progress = ?2 WHERE mint = ?1",
+        params![mint, progress],
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use xai_events::Pubkey;
+
+    use super::*;
+
+    fn buy(mint: Pubkey, new_virtual_sol_reserve: u128, timestamp: u64) -> XaiEvent {
+        XaiEvent::Buy(TradeEvent {
+            user                     : Pubkey::new_unique(),
+            user_token_account       : Pubkey::new_unique(),
+            mint,
+            virtual_sol_reserve      : 30_000_000_000,
+            virtual_token_reserve    : 1_000_000_000,
+            new_virtual_sol_reserve,
+            new_virtual_token_reserve: 1_000_000_000,
+            fee                      : 10,
+            fee_numerator            : Some(100),
+            integrator               : None,
+            integrator_fee           : 0,
+            amount_in                : 1_000,
+            amount_out               : 100,
+            timestamp,
+        })
+    }
+
+    fn transaction(signature: &str, slot: u64, events: Vec<XaiEvent>) -> ProgramTransaction {
+        ProgramTransaction {
+            signature : signature.to_string(),
+            slot,
+            block_time: None,
+            events,
+        }
+    }
+
+    fn candle(db: &Db, mint: &Pubkey) -> (f64, f64, f64, f64, f64, i64) {
+        db.conn
+            .query_row(
+                "SELECT open, high, low, close, volume_sol, trades FROM candles
+                 WHERE mint = ?1 AND interval = '1m'",
+                params![mint.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn out_of_order_replay_keeps_open_and_close() {
+        let mint = Pubkey::new_unique();
+        let first = transaction("a", 10, vec![buy(mint, 1_000_000_000, 60), buy(mint, 2_000_000_000, 60)]);
+        let last = transaction("b", 12, vec![buy(mint, 3_000_000_000, 61)]);
+
+        let mut in_order = Db::open(Path::new(":memory:")).unwrap();
+        in_order.index(&first).unwrap();
+        in_order.index(&last).unwrap();
+
+        let mut reversed = Db::open(Path::new(":memory:")).unwrap();
+        reversed.index(&last).unwrap();
+        reversed.index(&first).unwrap();
+
+        assert_eq!(candle(&in_order, &mint), (1.0, 3.0, 1.0, 3.0, 3_000.0, 3));
+        assert_eq!(candle(&reversed, &mint), candle(&in_order, &mint));
+    }
+
+    #[test]
+    fn reindex_is_idempotent() {
+        let mint = Pubkey::new_unique();
+        let buy = transaction("a", 10, vec![buy(mint, 1_000_000_000, 60)]);
+        let mut db = Db::open(Path::new(":memory:")).unwrap();
+
+        assert_eq!(db.index(&buy).unwrap(), 1);
+        let indexed = candle(&db, &mint);
+        assert_eq!(db.index(&buy).unwrap(), 0);
+
+        assert_eq!(candle(&db, &mint), indexed);
+        let balance: i64 = db.conn
+            .query_row("SELECT balance FROM holders WHERE mint = ?1", params![mint.to_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(balance, 100);
+    }
+
+    #[test]
+    fn sol_volume_is_gross_on_both_sides() {
+        let mint = Pubkey::new_unique();
+        let XaiEvent::Buy(mut sell) = buy(mint, 1_000_000_000, 60) else { unreachable!() };
+        sell.amount_in  = 100;
+        sell.amount_out = 990;
+        let mut db = Db::open(Path::new(":memory:")).unwrap();
+
+        db.index(&transaction("a", 10, vec![buy(mint, 1_000_000_000, 60), XaiEvent::Sell(sell)])).unwrap();
+
+        assert_eq!(candle(&db, &mint).4, 2_000.0);
+    }
+
+    fn reserves(db: &Db, mint: &Pubkey) -> (String, String) {
+        db.conn
+            .query_row(
+                "SELECT virtual_sol_reserve, virtual_token_reserve FROM curves WHERE mint = ?1",
+                params![mint.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn out_of_order_replay_keeps_the_latest_reserves() {
+        let mint = Pubkey::new_unique();
+        let first = transaction("a", 10, vec![buy(mint, 1_000_000_000, 60)]);
+        let last = transaction("b", 11, vec![buy(mint, 2_000_000_000, 60)]);
+        let mut db = Db::open(Path::new(":memory:")).unwrap();
+
+        db.index(&last).unwrap();
+        db.index(&first).unwrap();
+
+        assert_eq!(reserves(&db, &mint).0, "2000000000");
+    }
+
+    #[test]
+    fn referral_token_reward_moves_the_reserves() {
+        let mint = Pubkey::new_unique();
+        let reward = XaiEvent::ReferralTokenReward(xai_events::ReferralTokenRewardEvent {
+            recommender              : Pubkey::new_unique(),
+            mint,
+            sol_amount               : 1_000,
+            token_amount             : 10,
+            new_virtual_sol_reserve  : 1_000_001_000,
+            new_virtual_token_reserve: 999_999_990,
+        });
+        let mut db = Db::open(Path::new(":memory:")).unwrap();
+
+        assert_eq!(db.index(&transaction("a", 10, vec![buy(mint, 1_000_000_000, 60), reward])).unwrap(), 1);
+
+        assert_eq!(reserves(&db, &mint), ("1000001000".to_string(), "999999990".to_string()));
+    }
+}
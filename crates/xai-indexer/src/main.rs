@@ -0,0 +1,82 @@
+//! Indexes the trades of `xai-solana-program` into a SQLite database: trades,
+//! per-mint OHLCV candles, curve progress toward `sol_aim`, graduation times
+//! and net holder balances.
+
+mod db;
+mod rpc;
+mod transaction;
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use serde_json::Value;
+
+use crate::db::Db;
+use crate::rpc::Rpc;
+use crate::transaction::{parse_dump, parse_transaction};
+
+#[derive(Parser)]
+#[command(about = "Index xai-solana-program trades into SQLite")]
+struct Cli {
+    /// SQLite database, created if missing
+    #[arg(long, default_value = "xai-indexer.db")]
+    db: PathBuf,
+
+    /// First slot of the program build with CPI events, older transactions
+    /// are read from their `$NameEvent` log lines
+    #[arg(long, default_value_t = 0)]
+    cpi_events_slot: u64,
+
+    #[command(subcommand)]
+    source: Source,
+}
+
+#[derive(Subcommand)]
+enum Source {
+    /// `getTransaction` JSON dumps (encoding `json`), oldest first
+    Dump {
+        files: Vec<PathBuf>,
+    },
+    /// A local test validator, resuming after the last signature indexed from it
+    Rpc {
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+        /// Page size of `getSignaturesForAddress`
+        #[arg(long, default_value_t = 1000)]
+        limit: usize,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut db = Db::open(&cli.db)?;
+    let mut new_trades = 0;
+
+    match cli.source {
+        Source::Dump { files } => {
+            for file in files {
+                let dump: Value = serde_json::from_reader(BufReader::new(File::open(&file)?))
+                    .with_context(|| format!("reading {}", file.display()))?;
+                for transaction in parse_dump(&dump, cli.cpi_events_slot)? {
+                    new_trades += db.index(&transaction)?;
+                }
+            }
+        }
+        Source::Rpc { url, limit } => {
+            let rpc = Rpc::new(url);
+            for signature in rpc.signatures_since(db.cursor()?.as_deref(), limit)? {
+                if let Some(transaction) = parse_transaction(&rpc.transaction(&signature)?, cli.cpi_events_slot)? {
+                    new_trades += db.index(&transaction)?;
+                }
+                db.set_cursor(&signature)?;
+            }
+        }
+    }
+
+    println!("indexed {new_trades} new trades into {}", cli.db.display());
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use xai_events::PROGRAM_ID;
+
+/// `getTransaction` attempts before giving up on a signature.
+const TRANSACTION_ATTEMPTS: u32 = 5;
+
+/// Minimal JSON-RPC client, enough to page through the program's transactions.
+pub struct Rpc {
+    url: String,
+}
+
+impl Rpc {
+    pub fn new(url: String) -> Self {
+        Rpc { url }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let mut response: Value = ureq::post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id"     : 1,
+                "method" : method,
+                "params" : params,
+            }))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            bail!("{method} failed: {error}");
+        }
+
+        Ok(response["result"].take())
+    }
+
+    /// Signatures of the program's transactions after `until`, oldest first.
+    pub fn signatures_since(&self, until: Option<&str>, limit: usize) -> Result<Vec<String>> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+
+        loop {
+            let mut config = json!({ "limit": limit, "commitment": "confirmed" });
+            if let Some(before) = &before {
+                config["before"] = json!(before);
+            }
+            if let Some(until) = until {
+                config["until"] = json!(until);
+            }
+
+            let page = self.call("getSignaturesForAddress", json!([PROGRAM_ID.to_string(), config]))?;
+            let page: Vec<String> = page
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|info| info["signature"].as_str().map(str::to_string))
+                .collect();
+
+            let last_page = page.len() < limit;
+            before = page.last().cloned();
+            signatures.extend(page);
+            if last_page {
+                break;
+            }
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    /// The transaction of a listed signature. A node can return null for one
+    /// it has not stored yet, so retry and then fail rather than skip it and
+    /// move the cursor past its trades.
+    pub fn transaction(&self, signature: &str) -> Result<Value> {
+        for attempt in 0..TRANSACTION_ATTEMPTS {
+            if attempt > 0 {
+                thread::sleep(Duration::from_millis(500 << attempt));
+            }
+            let transaction = self.call("getTransaction", json!([signature, {
+                "encoding"                      : "json",
+                "commitment"                    : "confirmed",
+                "maxSupportedTransactionVersion": 0,
+            }]))?;
+            if !transaction.is_null() {
+                return Ok(transaction);
+            }
+        }
+
+        bail!("getTransaction returned null for {signature} after {TRANSACTION_ATTEMPTS} attempts")
+    }
+}
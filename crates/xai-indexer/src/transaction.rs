@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use xai_events::{decode_cpi_base58, decode_logs, XaiEvent, PROGRAM_ID};
+
+/// The program events of one successful transaction.
+#[derive(Debug)]
+pub struct ProgramTransaction {
+    pub signature : String,
+    pub slot      : u64,
+    pub block_time: Option<i64>,
+    pub events    : Vec<XaiEvent>,
+}
+
+/// Read `getTransaction` results with `"encoding": "json"`, either bare, wrapped
+/// in a JSON-RPC response, or as an array of those.
+pub fn parse_dump(dump: &Value, cpi_events_slot: u64) -> Result<Vec<ProgramTransaction>> {
+    let mut transactions = Vec::new();
+    match dump {
+        Value::Array(items) => {
+            for item in items {
+                transactions.extend(parse_dump(item, cpi_events_slot)?);
+            }
+        }
+        Value::Object(object) if object.contains_key("result") => {
+            transactions.extend(parse_dump(&object["result"], cpi_events_slot)?);
+        }
+        Value::Null => {}
+        _ => transactions.extend(parse_transaction(dump, cpi_events_slot)?),
+    }
+
+    Ok(transactions)
+}
+
+/// Missing or failed transactions and transactions without events give `None`.
+/// Events are read from the inner instructions from `cpi_events_slot` on and
+/// from the legacy log lines before it.
+pub fn parse_transaction(transaction: &Value, cpi_events_slot: u64) -> Result<Option<ProgramTransaction>> {
+    let meta = &transaction["meta"];
+    if transaction.is_null() || !meta["err"].is_null() {
+        return Ok(None);
+    }
+
+    let signature = transaction["transaction"]["signatures"][0]
+        .as_str()
+        .ok_or_else(|| anyhow!("transaction without signature"))?
+        .to_string();
+    let slot = transaction["slot"].as_u64().unwrap_or_default();
+    let block_time = transaction["blockTime"].as_i64();
+
+    // a build with `legacy-json-events` emits both, only one of them is read
+    let events = if slot >= cpi_events_slot {
+        cpi_events(transaction)
+    } else {
+        log_events(meta)
+    };
+    let events = events.with_context(|| format!("decoding {signature}"))?;
+
+    if events.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ProgramTransaction {
+        signature,
+        slot,
+        block_time,
+        events,
+    }))
+}
+
+fn log_events(meta: &Value) -> Result<Vec<XaiEvent>> {
+    let logs: Vec<&str> = meta["logMessages"]
+        .as_array()
+        .map(|logs| logs.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    Ok(decode_logs(&logs)?)
+}
+
+/// Event instructions are inner instructions of the program itself, other
+/// programs may use the same event names.
+fn cpi_events(transaction: &Value) -> Result<Vec<XaiEvent>> {
+    let meta = &transaction["meta"];
+    let account_keys: Vec<&str> = transaction["transaction"]["message"]["accountKeys"]
+        .as_array()
+        .into_iter()
+        .chain(meta["loadedAddresses"]["writable"].as_array())
+        .chain(meta["loadedAddresses"]["readonly"].as_array())
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let program_id = PROGRAM_ID.to_string();
+
+    let mut events = Vec::new();
+    for inner in meta["innerInstructions"].as_array().into_iter().flatten() {
+        for instruction in inner["instructions"].as_array().into_iter().flatten() {
+            let program_index = instruction["programIdIndex"].as_u64().unwrap_or(u64::MAX) as usize;
+            if account_keys.get(program_index) != Some(&program_id.as_str()) {
+                continue;
+            }
+
+            let data = instruction["data"].as_str().unwrap_or_default();
+            if let Some(event) = decode_cpi_base58(data)? {
+                events.push(event);
+            }
+        }
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use solana_program::keccak;
+
+use crate::instructions::*;
+
+/// Airdrop reserved at launch. Recipients claim with a proof against
+/// `merkle_root`; tokens sit in the ATA of this PDA.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct Airdrop {
+    pub mint          : Pubkey,
+    pub merkle_root   : [u8; 32],
+    pub total_amount  : u64,
+    pub claimed_amount: u64,
+    pub expires_at    : i64,
+    pub recovered     : bool,
+}
+
+impl Airdrop {
+    /// leaf = keccak(index || claimant || amount)
+    pub fn leaf(index: u32, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+        keccak::hashv(&[
+            &index.to_le_bytes(),
+            claimant.as_ref(),
+            &amount.to_le_bytes(),
+        ]).0
+    }
+}
+
+/// One bit per airdrop index, `BITS` indexes per PDA.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct AirdropBitmap {
+    pub bits: [u8; 1024],
+}
+
+impl Default for AirdropBitmap {
+    fn default() -> Self {
+        Self { bits: [0; 1024] }
+    }
+}
+
+impl AirdropBitmap {
+    pub const BITS: u32 = 1024 * 8;
+
+    pub fn is_claimed(&self, index: u32) -> bool {
+        let bit = index % Self::BITS;
+        self.bits[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u32) {
+        let bit = index % Self::BITS;
+        self.bits[(bit / 8) as usize] |= 1 << (bit % 8);
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct ClaimAirdrop<'info> {
+    #[account(
+        mut,
+        seeds = [b"airdrop", token_mint.key().as_ref()],
+        bump,
+    )]
+    pub airdrop: Account<'info, Airdrop>,
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"airdrop_bitmap",
+            token_mint.key().as_ref(),
+            &(index / AirdropBitmap::BITS).to_le_bytes()
+        ],
+        bump,
+        payer = claimant,
+        space = ANCHOR_DISCRIMINATOR + AirdropBitmap::INIT_SPACE,
+    )]
+    pub airdrop_bitmap: Box<Account<'info, AirdropBitmap>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = airdrop,
+        associated_token::token_program = token_program,
+    )]
+    pub airdrop_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = token_mint,
+        associated_token::authority = claimant,
+        associated_token::token_program = token_program,
+    )]
+    pub claimant_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Burn the unclaimed airdrop after expiry. The tokens were never part of the
+/// curve's `token_reserve`, so burning keeps its reserves unchanged.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecoverAirdrop<'info> {
+    #[account(
+        mut,
+        seeds = [b"airdrop", token_mint.key().as_ref()],
+        bump,
+    )]
+    pub airdrop: Account<'info, Airdrop>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = airdrop,
+        associated_token::token_program = token_program,
+    )]
+    pub airdrop_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event]
+pub struct ClaimAirdropEvent {
+    pub claimant: Pubkey,
+    pub mint    : Pubkey,
+    pub index   : u32,
+    pub amount  : u64,
+}
+
+#[event]
+pub struct RecoverAirdropEvent {
+    pub mint  : Pubkey,
+    pub amount: u64,
+}
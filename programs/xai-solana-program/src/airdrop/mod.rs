@@ -0,0 +1,3 @@
+pub mod airdrop;
+
+pub use airdrop::*;
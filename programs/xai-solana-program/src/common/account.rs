@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::{Discriminator, Space};
+
+use crate::{error::SwapError, ANCHOR_DISCRIMINATOR};
+
+/// Deserialize a program account passed in `remaining_accounts`, checking its
+/// owner and discriminator.
+pub fn load_account<T: AccountDeserialize + Owner>(info: &AccountInfo) -> Result<T> {
+    require_keys_eq!(*info.owner, T::owner(), ErrorCode::AccountOwnedByWrongProgram);
+
+    let data = info.try_borrow_data()?;
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Write back an account read with `load_account`.
+pub fn store_account<T: AccountSerialize>(info: &AccountInfo, account: &T) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    account.try_serialize(&mut &mut data[..])
+}
+
+/// Create a program account at a PDA, like `init`, for accounts only known
+/// at runtime. An address that was already funded is topped up instead.
+pub fn create_account<'info>(
+    info          : &AccountInfo<'info>,
+    space         : usize,
+    signer_seeds  : &[&[u8]],
+    payer         : &Signer<'info>,
+    system_program: &Program<'info, System>,
+    program_id    : &Pubkey,
+) -> Result<()> {
+    let lamports = Rent::get()?.minimum_balance(space);
+    let signer = &[signer_seeds];
+
+    if info.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: payer.to_account_info(),
+                    to  : info.clone(),
+                },
+                signer,
+            ),
+            lamports,
+            space.try_into().unwrap(),
+            program_id,
+        );
+    }
+
+    let shortfall = lamports.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to  : info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Allocate { account_to_allocate: info.clone() },
+            signer,
+        ),
+        space.try_into().unwrap(),
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Assign { account_to_assign: info.clone() },
+            signer,
+        ),
+        program_id,
+    )
+}
+
+/// Grow a program account to `space`, topping its rent up from `payer`. The
+/// new bytes are zeroed.
+pub fn realloc_account<'info>(
+    info          : &AccountInfo<'info>,
+    space         : usize,
+    payer         : &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to  : info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    info.realloc(space, true)?;
+
+    Ok(())
+}
+
+/// Whether an account of type `T` still has its `legacy_space` layout. Accounts
+/// already at the current size need no migration; any other size is rejected.
+fn is_legacy<T: Discriminator + Owner + Space>(info: &AccountInfo, legacy_space: usize) -> Result<bool> {
+    require_keys_eq!(*info.owner, T::owner(), ErrorCode::AccountOwnedByWrongProgram);
+    require!(
+        info.try_borrow_data()?.starts_with(&T::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+
+    if info.data_len() == ANCHOR_DISCRIMINATOR + T::INIT_SPACE {
+        return Ok(false);
+    }
+    require!(info.data_len() == legacy_space, SwapError::UnknownAccountLayout);
+
+    Ok(true)
+}
+
+/// Migrate an account of type `T` whose layout only gained trailing fields
+/// since it was `legacy_space` bytes long; the new fields read as zero.
+/// Accounts already at the current size are left as they are.
+pub fn migrate_appended<'info, T: Discriminator + Owner + Space>(
+    info          : &AccountInfo<'info>,
+    legacy_space  : usize,
+    payer         : &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if !is_legacy::<T>(info, legacy_space)? {
+        return Ok(());
+    }
+
+    realloc_account(info, ANCHOR_DISCRIMINATOR + T::INIT_SPACE, payer, system_program)
+}
+
+/// Migrate an account of type `T` whose fields moved since it was stored as
+/// `L`, `legacy_space` bytes long: read it as `L`, grow it and rewrite it as `T`.
+pub fn migrate_rewritten<'info, T, L>(
+    info          : &AccountInfo<'info>,
+    legacy_space  : usize,
+    payer         : &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()>
+where
+    T: AccountSerialize + Discriminator + Owner + Space + From<L>,
+    L: AnchorDeserialize,
+{
+    if !is_legacy::<T>(info, legacy_space)? {
+        return Ok(());
+    }
+
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        L::deserialize(&mut &data[ANCHOR_DISCRIMINATOR..])?
+    };
+
+    realloc_account(info, ANCHOR_DISCRIMINATOR + T::INIT_SPACE, payer, system_program)?;
+    store_account(info, &T::from(legacy))
+}
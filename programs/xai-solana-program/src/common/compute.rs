@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+/// Log `label` and the remaining compute units, only with the `compute-units` feature.
+/// The difference between two lines of a transaction is what the code in between used.
+#[inline(always)]
+pub fn log_compute_units(_label: &str) {
+    #[cfg(feature = "compute-units")]
+    {
+        msg!(_label);
+        anchor_lang::solana_program::log::sol_log_compute_units();
+    }
+}
@@ -0,0 +1,17 @@
+use solana_program::keccak;
+
+/// Verify a merkle proof built with sorted pairs, so the proof does not need
+/// to carry left/right positions.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed_hash = leaf;
+
+    for proof_element in proof.iter() {
+        computed_hash = if computed_hash <= *proof_element {
+            keccak::hashv(&[&computed_hash, proof_element]).0
+        } else {
+            keccak::hashv(&[proof_element, &computed_hash]).0
+        };
+    }
+
+    computed_hash == root
+}
@@ -1,5 +1,11 @@
+pub mod account;
+pub mod compute;
+pub mod merkle;
 pub mod sol;
 pub mod spl_token;
 
+pub use account::*;
+pub use compute::*;
+pub use merkle::*;
 pub use sol::*;
-pub use spl_token::*;
\ No newline at end of file
+pub use spl_token::*;
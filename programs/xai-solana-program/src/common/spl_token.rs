@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer as SplTransfer};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_spl::token_interface::{self, TransferChecked};
+use spl_token_2022::extension::{
+    transfer_fee::{self, TransferFeeConfig}, BaseStateWithExtensions, StateWithExtensions,
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct SplToken;
@@ -10,59 +14,144 @@ impl SplToken {
         from         : &Signer<'info>,
         from_ata     : &AccountInfo<'info>,
         to_ata       : &AccountInfo<'info>,
+        mint         : &AccountInfo<'info>,
+        decimals     : u8,
         amount       : u64,
         token_program: &AccountInfo<'info>,
     ) -> Result<bool> {
-        let cpi_accounts = SplTransfer {
+        let cpi_accounts = TransferChecked {
             from     : from_ata.to_account_info().clone(),
+            mint     : mint.to_account_info().clone(),
             to       : to_ata.to_account_info().clone(),
             authority: from.to_account_info().clone(),
         };
 
         let cpi_program = token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
 
         Ok(true)
     }
 
-    /// 
+    ///
     /// let spl_token = SplToken {};
-    /// 
+    ///
     /// let program_signer_seeds = &[
     ///     "program_signer".as_bytes(),
     ///     &[ctx.bumps.program_signer]
     /// ];
-    /// 
+    ///
     /// spl_token.transfer_from_pda(
     ///     program_signer_seeds,
     ///     &ctx.accounts.vault.to_account_info(),
     ///     &ctx.accounts.program_signer,
     ///     &ctx.accounts.receiver_ata.to_account_info(),
+    ///     &ctx.accounts.token_mint.to_account_info(),
+    ///     ctx.accounts.token_mint.decimals,
     ///     amount_out.try_into().unwrap(),
     ///     &ctx.accounts.token_program,
     /// )?;
-    /// 
+    ///
     pub fn transfer_from_pda<'info>(
         &self,
         from_seeds_bump: &[&[u8]],
         from_ata       : &AccountInfo<'info>,
         authority      : &AccountInfo<'info>,
         to_ata         : &AccountInfo<'info>,
+        mint           : &AccountInfo<'info>,
+        decimals       : u8,
         amount         : u64,
         token_program  : &AccountInfo<'info>,
     ) -> Result<bool> {
         let signer_seeds = [&from_seeds_bump[..]];
 
-        let cpi_accounts = SplTransfer {
+        let cpi_accounts = TransferChecked {
             from     : from_ata.to_account_info().clone(),
+            mint     : mint.to_account_info().clone(),
             to       : to_ata.to_account_info().clone(),
             authority: authority.to_account_info().clone(),
         };
         let cpi_program = token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
 
         Ok(true)
     }
+
+    /// Balance of a classic or Token-2022 token account.
+    pub fn balance(&self, token_account: &AccountInfo) -> Result<u64> {
+        let account_data = token_account.try_borrow_data()?;
+        let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+
+        Ok(account_state.base.amount)
+    }
+
+    /// Token-2022 transfer fee withheld when moving `amount` of `mint`.
+    /// Always 0 for the classic token program or mints without the extension.
+    pub fn transfer_fee(&self, mint: &AccountInfo, amount: u64) -> Result<u64> {
+        if *mint.owner != spl_token_2022::ID {
+            return Ok(0);
+        }
+
+        let mint_data = mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+        let fee = match mint_state.get_extension::<TransferFeeConfig>() {
+            Ok(transfer_fee_config) => transfer_fee_config
+                .calculate_epoch_fee(Clock::get()?.epoch, amount)
+                .unwrap(),
+            Err(_) => 0,
+        };
+
+        Ok(fee)
+    }
+
+    /// Harvest the transfer fees withheld in `sources` into `mint`, then withdraw
+    /// all fees withheld in the mint to `to_ata`. Returns the amount withdrawn.
+    pub fn withdraw_withheld_fees<'info>(
+        &self,
+        authority_seeds_bump: &[&[u8]],
+        mint                : &AccountInfo<'info>,
+        to_ata              : &AccountInfo<'info>,
+        authority           : &AccountInfo<'info>,
+        sources             : &[AccountInfo<'info>],
+        token_program       : &AccountInfo<'info>,
+    ) -> Result<u64> {
+        if !sources.is_empty() {
+            let source_keys: Vec<&Pubkey> = sources.iter().map(|source| source.key).collect();
+            let mut account_infos = vec![mint.clone()];
+            account_infos.extend_from_slice(sources);
+
+            invoke(
+                &transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                    token_program.key,
+                    mint.key,
+                    &source_keys,
+                )?,
+                &account_infos,
+            )?;
+        }
+
+        let withheld: u64 = {
+            let mint_data = mint.try_borrow_data()?;
+            let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+            mint_state.get_extension::<TransferFeeConfig>()?.withheld_amount.into()
+        };
+
+        if withheld > 0 {
+            invoke_signed(
+                &transfer_fee::instruction::withdraw_withheld_tokens_from_mint(
+                    token_program.key,
+                    mint.key,
+                    to_ata.key,
+                    authority.key,
+                    &[],
+                )?,
+                &[mint.clone(), to_ata.clone(), authority.clone()],
+                &[authority_seeds_bump],
+            )?;
+        }
+
+        Ok(withheld)
+    }
 }
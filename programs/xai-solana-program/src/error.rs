@@ -14,5 +14,82 @@ pub enum SwapError {
     FeeRecommendRewardUninitialized,
     #[msg("FeeRecommendReward PDA error")]
     FeeRecommendRewardError,
+    #[msg("The two-step token launch is disabled")]
+    LegacyLaunchDisabled,
+    #[msg("Creator allocation exceeds the allowed amount")]
+    CreatorAllocationTooLarge,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Creator vesting accounts are required")]
+    CreatorVestingAccountsMissing,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Invalid airdrop")]
+    InvalidAirdrop,
+    #[msg("Airdrop accounts are required")]
+    AirdropAccountsMissing,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Airdrop already claimed")]
+    AirdropAlreadyClaimed,
+    #[msg("Airdrop has expired")]
+    AirdropExpired,
+    #[msg("Airdrop has not expired yet")]
+    AirdropNotExpired,
+    #[msg("Identifier account does not match the creator's next identifier")]
+    InvalidIdentifier,
+    #[msg("Trading has not started yet")]
+    TradingNotStarted,
+    #[msg("Invalid opening window limits")]
+    InvalidOpeningLimits,
+    #[msg("Buy exceeds the per-wallet cap of the opening window")]
+    OpeningBuyCapExceeded,
+    #[msg("Invalid allowlist")]
+    InvalidAllowlist,
+    #[msg("Buyer is not on the allowlist")]
+    NotAllowlisted,
+    #[msg("Buy exceeds the allowlist cap")]
+    AllowlistCapExceeded,
+    #[msg("Invalid max wallet bps")]
+    InvalidMaxWallet,
+    #[msg("Buy exceeds the max wallet holding")]
+    MaxWalletExceeded,
+    #[msg("Invalid launch fee schedule")]
+    InvalidLaunchFeeSchedule,
+    #[msg("Invalid creator fee")]
+    InvalidCreatorFee,
+    #[msg("Invalid sweep amount")]
+    InvalidSweepAmount,
+    #[msg("Invalid fee receivers")]
+    InvalidFeeReceivers,
+    #[msg("Fee receiver accounts do not match the fee config")]
+    FeeReceiverMismatch,
+    #[msg("Invalid integrator fee")]
+    InvalidIntegratorFee,
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    #[msg("Referral accounts do not match the bound upline")]
+    ReferralChainMismatch,
+    #[msg("Invalid batch claim accounts")]
+    InvalidBatchClaim,
+    #[msg("Referral stats account does not match the recommender and mint")]
+    ReferralStatsMismatch,
+    #[msg("Invalid recommend award list")]
+    InvalidRecommendAwardList,
+    #[msg("More recommenders passed than referral tiers")]
+    TooManyRecommenders,
+    #[msg("Invalid claim window")]
+    InvalidClaimWindow,
+    #[msg("Referral reward is still within its claim window")]
+    RecommendRewardNotStale,
+    #[msg("Transfer fee source accounts must be writable")]
+    InvalidTransferFeeSource,
+    #[msg("The trader cannot be its own integrator")]
+    SelfIntegrator,
+    #[msg("Referral token vault is required for rewards in the token")]
+    ReferralTokenVaultMissing,
+    #[msg("Account data does not match a known layout")]
+    UnknownAccountLayout,
+    #[msg("Curve creator is already set")]
+    CreatorAlreadySet,
 }
-
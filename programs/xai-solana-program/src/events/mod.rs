@@ -0,0 +1,249 @@
+use anchor_lang::prelude::*;
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::Event;
+
+pub use xai_event_types::*;
+
+/// Emits events through a self-CPI like `emit_cpi!`, for code that runs
+/// outside of the handler and has no `ctx`.
+pub struct EventCpi<'info> {
+    pub event_authority: AccountInfo<'info>,
+    pub bump           : u8,
+}
+
+impl<'info> EventCpi<'info> {
+    pub fn new(event_authority: AccountInfo<'info>, bump: u8) -> Self {
+        Self { event_authority, bump }
+    }
+
+    /// Emit `event` as an inner instruction and, with `legacy-json-events`,
+    /// as the `$NameEvent: {..}` log line the old indexer reads.
+    pub fn emit<E: Event + LegacyLog>(&self, event: &E) -> Result<()> {
+        #[cfg(feature = "legacy-json-events")]
+        event.log();
+
+        let ix_data: Vec<u8> = EVENT_IX_TAG_LE.into_iter().chain(event.data()).collect();
+
+        let ix = Instruction::new_with_bytes(
+            crate::ID,
+            &ix_data,
+            vec![AccountMeta::new_readonly(*self.event_authority.key, true)],
+        );
+        invoke_signed(
+            &ix,
+            &[self.event_authority.clone()],
+            &[&[b"__event_authority", &[self.bump]]],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Legacy JSON form of an event, see `legacy`.
+pub trait LegacyLog {
+    fn log(&self) {}
+}
+
+#[cfg(not(feature = "legacy-json-events"))]
+impl<E: Event> LegacyLog for E {}
+
+/// `msg!` JSON logs with short keys, as emitted before the typed events.
+#[cfg(feature = "legacy-json-events")]
+pub mod legacy {
+    use anchor_lang::prelude::*;
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::LegacyLog;
+
+    #[derive(Debug, Serialize)]
+    pub struct CreateTokenEvent {
+        pub id  : String,   // identifier
+        pub u   : String,   // user
+        pub mint: String,   // token_mint
+        pub aa  : u64,      // airdrop_amount
+        pub ms  : u128,     // max_supply
+        pub ts  : u64,      // total_supply
+        pub rs  : u128,     // init_virtual_sol_reserve
+        pub rt  : u128,     // init_virtual_token_reserve
+        pub db  : u128,     // dev_buy_amount_in
+        pub dbo : u128,     // dev_buy_amount_out
+        pub sa  : u64,      // trading_starts_at
+        pub ss  : bool,     // trading_start_is_slot
+        pub ae  : u64,      // allowlist_ends_at
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct BuyEvent {
+        pub u   : String,   // user
+        pub ua  : String,   // user's token account
+        pub mint: String,   // token_mint
+        pub vsr : u128,     // virtual_sol_reserve
+        pub vtr : u128,     // virtual_token_reserve
+        pub nvsr: u128,     // new_virtual_sol_reserve
+        pub nvtr: u128,     // new_virtual_token_reserve
+        pub f   : u128,     // fee
+        pub fr  : u64,      // fee numerator applied, over trade_fee_denominator
+        pub ig  : Option<String>, // integrator
+        pub igf : u64,      // integrator fee
+        pub i   : u128,     // amount_in
+        pub o   : u128,     // amount_out
+        pub t   : u64,      // timestamp
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct TokenGraduated {
+        pub mint: String,
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct SellEvent {
+        pub u   : String,   // user
+        pub ua  : String,   // user's token account
+        pub mint: String,   // token_mint
+        pub vsr : u128,     // virtual_sol_reserve
+        pub vtr : u128,     // virtual_token_reserve
+        pub nvsr: u128,     // new_virtual_sol_reserve
+        pub nvtr: u128,     // new_virtual_token_reserve
+        pub f   : u128,     // fee
+        pub fr  : u64,      // fee numerator applied, over trade_fee_denominator
+        pub ig  : Option<String>, // integrator
+        pub igf : u64,      // integrator fee
+        pub i   : u128,     // amount_in
+        pub o   : u128,     // amount_out
+        pub t   : u64,      // timestamp
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct CreatorFeeEvent {
+        pub mint: String,   // token_mint
+        pub c   : String,   // creator
+        pub a   : u64,      // amount accrued
+        pub uc  : u64,      // unclaimed_sol
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct ReferralRewardEvent {
+        pub u   : String,       // user
+        pub mint: String,       // token_mint
+        pub r   : Vec<String>,  // recommenders paid, closest first
+        pub a   : Vec<u64>,     // reward of each recommender
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct ReferralTokenRewardEvent {
+        pub r   : String,   // recommender
+        pub mint: String,   // token_mint
+        pub s   : u64,      // SOL reward spent
+        pub o   : u64,      // tokens received
+        pub nvsr: u128,     // new_virtual_sol_reserve
+        pub nvtr: u128,     // new_virtual_token_reserve
+    }
+
+    impl LegacyLog for super::CreateTokenEvent {
+        fn log(&self) {
+            msg!("$CreateTokenEvent: {}", json!(CreateTokenEvent {
+                id  : self.identifier.to_string(),
+                u   : self.user.to_string(),
+                mint: self.mint.to_string(),
+                aa  : self.airdrop_amount,
+                ms  : self.max_supply,
+                ts  : self.total_supply,
+                rs  : self.init_virtual_sol_reserve,
+                rt  : self.init_virtual_token_reserve,
+                db  : self.dev_buy_amount_in,
+                dbo : self.dev_buy_amount_out,
+                sa  : self.trading_starts_at,
+                ss  : self.trading_start_is_slot,
+                ae  : self.allowlist_ends_at,
+            }));
+        }
+    }
+
+    impl LegacyLog for super::BuyEvent {
+        fn log(&self) {
+            msg!("$BuyEvent: {}", json!(BuyEvent {
+                u   : self.user.to_string(),
+                ua  : self.user_token_account.to_string(),
+                mint: self.mint.to_string(),
+                vsr : self.virtual_sol_reserve,
+                vtr : self.virtual_token_reserve,
+                nvsr: self.new_virtual_sol_reserve,
+                nvtr: self.new_virtual_token_reserve,
+                f   : self.fee,
+                fr  : self.fee_numerator,
+                ig  : self.integrator.map(|integrator| integrator.to_string()),
+                igf : self.integrator_fee,
+                i   : self.amount_in,
+                o   : self.amount_out,
+                t   : self.timestamp,
+            }));
+        }
+    }
+
+    impl LegacyLog for super::SellEvent {
+        fn log(&self) {
+            msg!("$SellEvent: {}", json!(SellEvent {
+                u   : self.user.to_string(),
+                ua  : self.user_token_account.to_string(),
+                mint: self.mint.to_string(),
+                vsr : self.virtual_sol_reserve,
+                vtr : self.virtual_token_reserve,
+                nvsr: self.new_virtual_sol_reserve,
+                nvtr: self.new_virtual_token_reserve,
+                f   : self.fee,
+                fr  : self.fee_numerator,
+                ig  : self.integrator.map(|integrator| integrator.to_string()),
+                igf : self.integrator_fee,
+                i   : self.amount_in,
+                o   : self.amount_out,
+                t   : self.timestamp,
+            }));
+        }
+    }
+
+    impl LegacyLog for super::TokenGraduatedEvent {
+        fn log(&self) {
+            msg!("$TokenGraduatedEvent: {}", json!(TokenGraduated {
+                mint: self.mint.to_string(),
+            }));
+        }
+    }
+
+    impl LegacyLog for super::CreatorFeeEvent {
+        fn log(&self) {
+            msg!("$CreatorFeeEvent: {}", json!(CreatorFeeEvent {
+                mint: self.mint.to_string(),
+                c   : self.creator.to_string(),
+                a   : self.amount,
+                uc  : self.unclaimed_sol,
+            }));
+        }
+    }
+
+    impl LegacyLog for super::ReferralRewardEvent {
+        fn log(&self) {
+            msg!("$ReferralRewardEvent: {}", json!(ReferralRewardEvent {
+                u   : self.user.to_string(),
+                mint: self.mint.to_string(),
+                r   : self.recommenders.iter().map(|recommender| recommender.to_string()).collect(),
+                a   : self.rewards.clone(),
+            }));
+        }
+    }
+
+    impl LegacyLog for super::ReferralTokenRewardEvent {
+        fn log(&self) {
+            msg!("$ReferralTokenRewardEvent: {}", json!(ReferralTokenRewardEvent {
+                r   : self.recommender.to_string(),
+                mint: self.mint.to_string(),
+                s   : self.sol_amount,
+                o   : self.token_amount,
+                nvsr: self.new_virtual_sol_reserve,
+                nvtr: self.new_virtual_token_reserve,
+            }));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+/// Per-mint vault of the creator's share of trade fees. The SOL is held by
+/// the PDA itself, `unclaimed_sol` of it is owed to `creator`.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct CreatorVault {
+    pub mint         : Pubkey,
+    pub creator      : Pubkey,
+    pub unclaimed_sol: u64,
+    pub total_earned : u64,
+}
+
+impl CreatorVault {
+    pub fn accrue(&mut self, amount: u64) {
+        self.unclaimed_sol = self.unclaimed_sol.checked_add(amount).unwrap();
+        self.total_earned  = self.total_earned.checked_add(amount).unwrap();
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreatorClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_vault", creator_vault.mint.as_ref()],
+        bump,
+        has_one = creator,
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct CreatorClaimEvent {
+    pub creator     : Pubkey,
+    pub mint        : Pubkey,
+    pub claim_amount: u64,
+    pub total_earned: u64,
+}
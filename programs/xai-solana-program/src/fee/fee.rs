@@ -1,5 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::SwapError;
+use anchor_lang::Discriminator;
+use crate::{
+    common::{create_account, load_account, realloc_account, store_account},
+    events::{EventCpi, ReferralRewardEvent},
+    fee::{ReferralBinding, ReferralStats, TokenReward},
+    SwapError, ANCHOR_DISCRIMINATOR,
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct Fees {
@@ -15,6 +21,51 @@ pub struct Fees {
 pub struct FeeRecommendReward {
     pub unclaimed_sol: u64,
     pub total_reward : u64,
+    /// Start of the claim window, reset on every claim
+    pub last_claim_at: i64,
+}
+
+impl FeeRecommendReward {
+    /// Bring the account of `recommender` at `info` to the current layout, paid by `payer`:
+    /// create it, or grow one written before `last_claim_at`. Its claim window starts now.
+    pub fn migrate<'info>(
+        info          : &AccountInfo<'info>,
+        recommender   : &Pubkey,
+        payer         : &Signer<'info>,
+        system_program: &Program<'info, System>,
+        program_id    : &Pubkey,
+    ) -> Result<()> {
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"fee_recommend_reward", recommender.as_ref()],
+            program_id,
+        );
+        require!(info.key() == address, SwapError::FeeRecommendRewardError);
+
+        let space = ANCHOR_DISCRIMINATOR + Self::INIT_SPACE;
+        if info.data_len() >= space {
+            return Ok(());
+        }
+
+        let mut fee_recommend_reward = Self::default();
+        if info.data_len() == 0 {
+            create_account(
+                info,
+                space,
+                &[b"fee_recommend_reward", recommender.as_ref(), &[bump]],
+                payer,
+                system_program,
+                program_id,
+            )?;
+        } else {
+            require!(info.owner == program_id, SwapError::FeeRecommendRewardError);
+
+            realloc_account(info, space, payer, system_program)?;
+            fee_recommend_reward = load_account(info)?;
+        }
+
+        fee_recommend_reward.last_claim_at = Clock::get()?.unix_timestamp;
+        store_account(info, &fee_recommend_reward)
+    }
 }
 
 impl Fees {
@@ -43,53 +94,144 @@ impl Fees {
         }
     }
 
-    pub fn reward_recommend(
+    /// Pay referral rewards up the trader's bound upline.
+    ///
+    /// `remaining_accounts` holds, per level, `[referral_binding, recommender, fee_recommend_reward]`,
+    /// starting with the binding of `user`, each optionally followed by the recommender's
+    /// `referral_stats` for `mint`. Each level must match the stored chain. A chain shorter
+    /// than `recommend_reward_list` is closed by the uninitialized binding of its last member.
+    /// `fee_recommend_reward` is migrated if needed, paid by `user`. Stats are only updated
+    /// when passed; recommenders create them with `initialize_referral_stats`.
+    /// `new_referee` counts `user` as a new referee of the first level's stats.
+    ///
+    /// With `token_rewards`, levels whose stats opted into `reward_in_token` are pushed there
+    /// instead of being credited in SOL; they are still taken from the residue fee.
+    pub fn reward_recommend<'info>(
         &self, 
         trading_fee: u64, 
-        remaining_accounts: &[AccountInfo], 
-        recommend_reward_list: &[u16; 5],
+        user: &Signer<'info>,
+        mint: &Pubkey,
+        new_referee: bool,
+        remaining_accounts: &[AccountInfo<'info>], 
+        recommend_reward_list: &[u16],
+        mut token_rewards: Option<&mut Vec<TokenReward>>,
+        event_cpi: &EventCpi,
+        system_program: &Program<'info, System>,
         program_id: &Pubkey,
     ) -> Result<(u64, u64)> {
         let mut total_reward_fee: u64 = 0;
-        let recommend_len = remaining_accounts.len() / 2;
-
-        let (recommend_accounts, recommend_pdas) = remaining_accounts.split_at(recommend_len);
-
-        for i in 0..recommend_len {
-            let recommend_account = &recommend_accounts[i];
-            let recommend_account_pda = &recommend_pdas[i];
+        let mut total_token_reward_fee: u64 = 0;
+
+        let mut referee = user.key();
+        let mut paid_recommenders: Vec<Pubkey> = Vec::with_capacity(recommend_reward_list.len());
+        let mut paid_rewards: Vec<u64> = Vec::with_capacity(recommend_reward_list.len());
+
+        let mut cursor = 0;
+        for (i, recommend_reward) in recommend_reward_list.iter().enumerate() {
+            // the upline must not be cut short, mirrors `bind_referrer`
+            let binding_account = remaining_accounts.get(cursor).ok_or(SwapError::ReferralChainMismatch)?;
+            let binding = match ReferralBinding::load(binding_account, &referee, program_id)? {
+                Some(binding) => binding,
+                None => {
+                    cursor += 1;
+                    break;
+                }
+            };
+
+            require!(remaining_accounts.len() >= cursor + 3, SwapError::ReferralChainMismatch);
+            let recommend_account = &remaining_accounts[cursor + 1];
+            let recommend_account_pda = &remaining_accounts[cursor + 2];
+            cursor += 3;
+
+            // the recommender must be the one `referee` bound to
+            require!(
+                binding.referrer == recommend_account.key() && recommend_account.key() != user.key(),
+                SwapError::ReferralChainMismatch
+            );
+            referee = recommend_account.key();
+
+            // per-mint attribution, when the recommender created its stats
+            let referral_stats_index = remaining_accounts
+                .get(cursor)
+                .filter(|info| info.key() == ReferralStats::address(&referee, mint, program_id))
+                .map(|_| cursor);
+            let mut referral_stats: Option<ReferralStats> = match referral_stats_index {
+                Some(index) => {
+                    cursor += 1;
+                    Some(load_account(&remaining_accounts[index])?)
+                }
+                None => None,
+            };
 
-            let recommend_reward = *recommend_reward_list.get(i).unwrap();
             let reward_fee = trading_fee
-                .checked_mul(u64::try_from(recommend_reward).ok().unwrap()).unwrap()
+                .checked_mul(u64::from(*recommend_reward)).unwrap()
                 .checked_div(10000).unwrap();
 
-            total_reward_fee = total_reward_fee.checked_add(reward_fee).unwrap();
-
-            let (expect_fee_recommend_reward_pda, _) = Pubkey::find_program_address(
-                &[b"fee_recommend_reward", recommend_account.key().as_ref()],
+            FeeRecommendReward::migrate(
+                recommend_account_pda,
+                &recommend_account.key(),
+                user,
+                system_program,
                 program_id,
-            );
-
-            require!(expect_fee_recommend_reward_pda.key() == recommend_account_pda.key(), SwapError::FeeRecommendRewardError);
-            require!(recommend_account_pda.data_len() > 0, SwapError::FeeRecommendRewardUninitialized);
-            
-            let mut fee_recommend_reward_data = recommend_account_pda.try_borrow_mut_data()?;
-
-            let fee_recommend_reward_data = &mut fee_recommend_reward_data[8..];
+            )?;
+
+            let in_token = token_rewards.is_some()
+                && referral_stats.as_ref().map_or(false, |referral_stats| referral_stats.reward_in_token);
+
+            if in_token {
+                token_rewards.as_mut().unwrap().push(TokenReward {
+                    referral_stats_index: referral_stats_index.unwrap(),
+                    sol                 : reward_fee,
+                });
+                total_token_reward_fee = total_token_reward_fee.checked_add(reward_fee).unwrap();
+            } else {
+                total_reward_fee = total_reward_fee.checked_add(reward_fee).unwrap();
+
+                let mut fee_recommend_reward_data = recommend_account_pda.try_borrow_mut_data()?;
+                require!(
+                    fee_recommend_reward_data[..8] == FeeRecommendReward::DISCRIMINATOR,
+                    SwapError::FeeRecommendRewardError
+                );
+
+                let fee_recommend_reward_data = &mut fee_recommend_reward_data[8..];
             
-            let unclaimed_sol = u64::from_le_bytes(fee_recommend_reward_data[..8].try_into().unwrap());
-            let total_reward = u64::from_le_bytes(fee_recommend_reward_data[8..16].try_into().unwrap());
+                let unclaimed_sol = u64::from_le_bytes(fee_recommend_reward_data[..8].try_into().unwrap());
+                let total_reward = u64::from_le_bytes(fee_recommend_reward_data[8..16].try_into().unwrap());
             
-            let new_unclaimed_sol = unclaimed_sol.checked_add(reward_fee).unwrap();
-            let new_total_reward = total_reward.checked_add(reward_fee).unwrap();
+                let new_unclaimed_sol = unclaimed_sol.checked_add(reward_fee).unwrap();
+                let new_total_reward = total_reward.checked_add(reward_fee).unwrap();
             
-            fee_recommend_reward_data[..8].copy_from_slice(&new_unclaimed_sol.to_le_bytes());
-            fee_recommend_reward_data[8..16].copy_from_slice(&new_total_reward.to_le_bytes());
+                fee_recommend_reward_data[..8].copy_from_slice(&new_unclaimed_sol.to_le_bytes());
+                fee_recommend_reward_data[8..16].copy_from_slice(&new_total_reward.to_le_bytes());
+            }
+
+            if let (Some(index), Some(referral_stats)) = (referral_stats_index, referral_stats.as_mut()) {
+                referral_stats.total_reward = referral_stats.total_reward.checked_add(reward_fee).unwrap();
+                referral_stats.trade_count += 1;
+                if i == 0 && new_referee {
+                    referral_stats.referee_count += 1;
+                }
+                store_account(&remaining_accounts[index], referral_stats)?;
+            }
+
+            paid_recommenders.push(recommend_account.key());
+            paid_rewards.push(reward_fee);
+        }
+        require!(cursor == remaining_accounts.len(), SwapError::ReferralChainMismatch);
+
+        if !paid_recommenders.is_empty() {
+            event_cpi.emit(&ReferralRewardEvent {
+                user        : user.key(),
+                mint        : *mint,
+                recommenders: paid_recommenders,
+                rewards     : paid_rewards,
+            })?;
         }
 
         Ok((
-            trading_fee.checked_sub(total_reward_fee).unwrap(),
+            trading_fee
+                .checked_sub(total_reward_fee).unwrap()
+                .checked_sub(total_token_reward_fee).unwrap(),
             total_reward_fee,
         ))
     }
@@ -1,5 +1,15 @@
+pub mod creator_fee;
 pub mod fee;
+pub mod protocol_fee;
 pub mod recommender_claim;
+pub mod referral;
+pub mod referral_token;
+pub mod transfer_fee;
 
+pub use creator_fee::*;
 pub use fee::*;
-pub use recommender_claim::*;
\ No newline at end of file
+pub use protocol_fee::*;
+pub use recommender_claim::*;
+pub use referral::*;
+pub use referral_token::*;
+pub use transfer_fee::*;
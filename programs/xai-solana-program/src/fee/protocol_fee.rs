@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::{FeeConfig, ProgramSystemAccount, ANCHOR_DISCRIMINATOR};
+
+/// Treasury of protocol trade and creation fees. The SOL is held by the PDA
+/// itself and swept to `fee_config.fee_receivers` by the admin.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct ProtocolFeeVault {
+    pub total_accrued: u64,
+    pub total_swept  : u64,
+}
+
+impl ProtocolFeeVault {
+    pub fn accrue(&mut self, amount: u64) {
+        self.total_accrued = self.total_accrued.checked_add(amount).unwrap();
+    }
+
+    /// Lamports that can be swept without going below the rent-exempt minimum.
+    pub fn sweepable(info: &AccountInfo) -> Result<u64> {
+        let rent_exempt = Rent::get()?.minimum_balance(info.data_len());
+        Ok(info.lamports().saturating_sub(rent_exempt))
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolFeeVault<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        init,
+        seeds = [b"protocol_fee_vault"],
+        bump,
+        payer = admin,
+        space = ANCHOR_DISCRIMINATOR + ProtocolFeeVault::INIT_SPACE,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
+    #[account(
+        mut,
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepProtocolFees<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_fee_vault"],
+        bump,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
+    /// `remaining_accounts` are the fee receivers, in `fee_config.fee_receivers()` order
+    #[account(
+        seeds = [b"fee_config"],
+        bump,
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+}
+
+#[event]
+pub struct ProtocolFeePaidEvent {
+    pub receiver: Pubkey,
+    pub bps     : u16,
+    pub amount  : u64,
+}
+
+#[event]
+pub struct SweepProtocolFeesEvent {
+    pub amount       : u64,
+    pub total_accrued: u64,
+    pub total_swept  : u64,
+}
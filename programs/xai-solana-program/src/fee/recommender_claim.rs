@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
-use crate::fee::FeeRecommendReward;
+use crate::{
+    fee::{FeeRecommendReward, ProtocolFeeVault},
+    FeeConfig, ProgramSystemAccount, ANCHOR_DISCRIMINATOR,
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RecommenderClaimSol<'info> {
     #[account(
@@ -22,3 +26,151 @@ pub struct RecommenderClaimSol<'info> {
     pub recommender: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
+
+/// Where a recommender's rewards are paid by `recommender_claim_sol_to_payout`
+/// and the batch claim. Set once by the recommender.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct RecommenderPayout {
+    pub recommender: Pubkey,
+    pub payout     : Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct SetRecommenderPayout<'info> {
+    #[account(
+        init,
+        seeds = [b"recommender_payout", recommender.key().as_ref()],
+        bump,
+        payer = recommender,
+        space = ANCHOR_DISCRIMINATOR + RecommenderPayout::INIT_SPACE,
+    )]
+    pub recommender_payout: Account<'info, RecommenderPayout>,
+
+    /// CHECK: only recorded as the payout address
+    pub payout: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub recommender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Anyone can push a recommender's rewards to its payout address.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecommenderClaimSolToPayout<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_recommend_reward", recommender_payout.recommender.as_ref()],
+        bump,
+    )]
+    pub fee_recommend_reward: Account<'info, FeeRecommendReward>,
+
+    #[account(
+        seeds = [b"recommender_payout", recommender_payout.recommender.as_ref()],
+        bump,
+        has_one = payout,
+    )]
+    pub recommender_payout: Account<'info, RecommenderPayout>,
+
+    /// CHECK: Send SOL to recommender
+    #[account(
+        mut,
+        seeds = [b"recommend_reward_vault"],
+        bump,
+    )]
+    pub recommend_reward_vault: AccountInfo<'info>,
+
+    /// CHECK: checked against `recommender_payout`
+    #[account(mut)]
+    pub payout: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// `remaining_accounts` holds, per recommender, `[fee_recommend_reward, recommender_payout, payout]`.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BatchRecommenderClaimSol<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    /// CHECK: Send SOL to recommender
+    #[account(
+        mut,
+        seeds = [b"recommend_reward_vault"],
+        bump,
+    )]
+    pub recommend_reward_vault: AccountInfo<'info>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+}
+
+/// Move a recommender's unclaimed rewards to the protocol treasury once its
+/// claim window has passed.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepStaleRecommendReward<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        seeds = [b"fee_config"],
+        bump,
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_recommend_reward", recommender.key().as_ref()],
+        bump,
+    )]
+    pub fee_recommend_reward: Account<'info, FeeRecommendReward>,
+
+    /// CHECK: Only used in seeds
+    pub recommender: UncheckedAccount<'info>,
+
+    /// CHECK: Send SOL to recommender
+    #[account(
+        mut,
+        seeds = [b"recommend_reward_vault"],
+        bump,
+    )]
+    pub recommend_reward_vault: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_fee_vault"],
+        bump,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+}
+
+#[event]
+pub struct StaleRecommendRewardSweptEvent {
+    pub recommender  : Pubkey,
+    pub amount       : u64,
+    pub last_claim_at: i64,
+}
+
+#[event]
+pub struct RecommenderPayoutClaimEvent {
+    pub recommender : Pubkey,
+    pub payout      : Pubkey,
+    pub claim_amount: u64,
+}
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::SwapError,
+    ANCHOR_DISCRIMINATOR,
+};
+
+/// Max referral levels paid on a trade, the length of `FeeConfig.recommend_award_list`.
+pub const MAX_REFERRAL_DEPTH: usize = 8;
+
+/// Who referred `user`. Written once by `bind_referrer`; the upline of a
+/// trader is the chain of these bindings.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct ReferralBinding {
+    pub user    : Pubkey,
+    pub referrer: Pubkey,
+    pub bound_at: i64,
+}
+
+impl ReferralBinding {
+    pub fn address(user: &Pubkey, program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"referral_binding", user.as_ref()], program_id).0
+    }
+
+    /// Read the binding of `user` from `info`, `None` if `user` has not bound a referrer.
+    pub fn load(info: &AccountInfo, user: &Pubkey, program_id: &Pubkey) -> Result<Option<Self>> {
+        require!(info.key() == Self::address(user, program_id), SwapError::ReferralChainMismatch);
+
+        if info.data_len() == 0 {
+            return Ok(None);
+        }
+        require!(info.owner == program_id, SwapError::ReferralChainMismatch);
+
+        let binding = Self::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        Ok(Some(binding))
+    }
+}
+
+/// What a recommender earned on one mint.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct ReferralStats {
+    pub recommender       : Pubkey,
+    pub mint              : Pubkey,
+    pub total_reward      : u64,
+    pub trade_count       : u64,
+    /// Direct referees that bought the mint
+    pub referee_count     : u32,
+    /// Take rewards on this mint in the token, bought on the curve, on trades that pass these stats
+    pub reward_in_token   : bool,
+    pub unclaimed_tokens  : u64,
+    pub total_token_reward: u64,
+}
+
+impl ReferralStats {
+    pub fn address(recommender: &Pubkey, mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"referral_stats", recommender.as_ref(), mint.as_ref()],
+            program_id,
+        ).0
+    }
+}
+
+/// Per-mint stats are opt-in: trades only update them once the recommender
+/// has created and funded them here.
+#[derive(Accounts)]
+pub struct InitializeReferralStats<'info> {
+    #[account(
+        init,
+        seeds = [b"referral_stats", recommender.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = recommender,
+        space = ANCHOR_DISCRIMINATOR + ReferralStats::INIT_SPACE,
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+
+    #[account(mut)]
+    pub recommender: Signer<'info>,
+
+    /// CHECK: Only used in seeds
+    pub mint: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// `remaining_accounts` are the `ReferralBinding` PDAs of the referrer's upline,
+/// in order, until one is uninitialized or `MAX_REFERRAL_DEPTH` is reached. The referrer's
+/// `FeeRecommendReward` is created here so that every bound upline can be paid.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BindReferrer<'info> {
+    #[account(
+        init,
+        seeds = [b"referral_binding", user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + ReferralBinding::INIT_SPACE,
+    )]
+    pub referral_binding: Account<'info, ReferralBinding>,
+
+    /// CHECK: only recorded as the referrer
+    pub referrer: UncheckedAccount<'info>,
+
+    /// CHECK: created or migrated by `FeeRecommendReward::migrate`
+    #[account(
+        mut,
+        seeds = [b"fee_recommend_reward", referrer.key().as_ref()],
+        bump,
+    )]
+    pub referrer_fee_recommend_reward: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct BindReferrerEvent {
+    pub user    : Pubkey,
+    pub referrer: Pubkey,
+}
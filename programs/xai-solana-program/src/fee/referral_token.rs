@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    bonding_curve::*,
+    common::{load_account, store_account, SplToken},
+    events::{EventCpi, ReferralTokenRewardEvent},
+    fee::ReferralStats,
+    CurveConfig, ANCHOR_DISCRIMINATOR,
+};
+
+/// A referral reward of `sol` for the recommender whose `ReferralStats` is
+/// `remaining_accounts[referral_stats_index]`, to be paid in the traded token.
+#[derive(Clone, Debug, Default)]
+pub struct TokenReward {
+    pub referral_stats_index: usize,
+    pub sol                 : u64,
+}
+
+/// Buy `token_rewards` on the curve at its post-trade state. The SOL stays in the
+/// curve, the tokens move to `referral_token_vault` and are owed in each level's
+/// `ReferralStats`. The caller makes sure the buys cannot graduate the curve.
+pub fn buy_token_rewards<'info>(
+    token_rewards       : &[TokenReward],
+    remaining_accounts  : &[AccountInfo],
+    curve_config        : &mut Account<'info, CurveConfig>,
+    vault               : &AccountInfo<'info>,
+    program_signer      : &AccountInfo<'info>,
+    program_signer_bump : u8,
+    referral_token_vault: &AccountInfo<'info>,
+    mint                : &AccountInfo<'info>,
+    decimals            : u8,
+    token_program       : &AccountInfo<'info>,
+    event_cpi           : &EventCpi<'info>,
+) -> Result<()> {
+    let spl_token = SplToken;
+
+    let program_signer_seeds = &[
+        "program_signer".as_bytes(),
+        &[program_signer_bump]
+    ];
+
+    for token_reward in token_rewards {
+        let (amount_out, new_virtual_sol_reserve, new_virtual_token_reserve) =
+            calculator::buy(&curve_config, token_reward.sol.into())?;
+
+        curve_config.sol_aim                = curve_config.sol_aim.checked_sub(token_reward.sol.into()).unwrap();
+        curve_config.sol_reserve           += u128::from(token_reward.sol);
+        curve_config.virtual_sol_reserve    = new_virtual_sol_reserve;
+        curve_config.virtual_token_reserve  = new_virtual_token_reserve;
+        curve_config.token_reserve         -= amount_out;
+
+        let amount_out: u64 = amount_out.try_into().unwrap();
+        let received = amount_out.checked_sub(spl_token.transfer_fee(mint, amount_out)?).unwrap();
+
+        spl_token.transfer_from_pda(
+            program_signer_seeds,
+            vault,
+            program_signer,
+            referral_token_vault,
+            mint,
+            decimals,
+            amount_out,
+            token_program,
+        )?;
+
+        let referral_stats_account = &remaining_accounts[token_reward.referral_stats_index];
+        let mut referral_stats: ReferralStats = load_account(referral_stats_account)?;
+        referral_stats.unclaimed_tokens   = referral_stats.unclaimed_tokens.checked_add(received).unwrap();
+        referral_stats.total_token_reward = referral_stats.total_token_reward.checked_add(received).unwrap();
+        store_account(referral_stats_account, &referral_stats)?;
+
+        event_cpi.emit(&ReferralTokenRewardEvent {
+            recommender : referral_stats.recommender,
+            mint        : mint.key(),
+            sol_amount  : token_reward.sol,
+            token_amount: received,
+            new_virtual_sol_reserve,
+            new_virtual_token_reserve,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Opt in or out of referral rewards in `token_mint`. Creates the stats and the
+/// mint's referral token vault if needed.
+#[derive(Accounts)]
+pub struct SetReferralRewardInToken<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"referral_stats", recommender.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        payer = recommender,
+        space = ANCHOR_DISCRIMINATOR + ReferralStats::INIT_SPACE,
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+
+    /// CHECK: owns the referral token vaults
+    #[account(seeds = [b"referral_token_authority"], bump)]
+    pub referral_token_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = recommender,
+        associated_token::mint = token_mint,
+        associated_token::authority = referral_token_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub referral_token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub recommender: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferralTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"referral_stats", recommender.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        has_one = recommender,
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+
+    /// CHECK: owns the referral token vaults
+    #[account(seeds = [b"referral_token_authority"], bump)]
+    pub referral_token_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = referral_token_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub referral_token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = recommender,
+        associated_token::mint = token_mint,
+        associated_token::authority = recommender,
+        associated_token::token_program = token_program,
+    )]
+    pub recommender_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub recommender: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct ClaimReferralTokensEvent {
+    pub recommender : Pubkey,
+    pub mint        : Pubkey,
+    pub claim_amount: u64,
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{FeeConfig, ProgramSystemAccount, ProtocolFeeVault};
+
+/// Collect the Token-2022 transfer fees of `token_mint` into the protocol fee
+/// vault's token account. `program_signer` is the mint's withdraw-withheld
+/// authority. `remaining_accounts` are the token accounts to harvest, writable.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawTransferFees<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        seeds = [b"protocol_fee_vault"],
+        bump,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
+    /// CHECK: withdraw-withheld authority of the mint
+    #[account(seeds = [b"program_signer"], bump)]
+    pub program_signer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = token_mint,
+        associated_token::authority = protocol_fee_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub protocol_fee_vault_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(address = spl_token_2022::ID)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Split the protocol fee vault's tokens of `token_mint` between the fee receivers.
+/// `remaining_accounts` are the receivers' token accounts, in `fee_config.fee_receivers()` order.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepProtocolTokenFees<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        seeds = [b"protocol_fee_vault"],
+        bump,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = protocol_fee_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub protocol_fee_vault_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"fee_config"],
+        bump,
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event]
+pub struct WithdrawTransferFeesEvent {
+    pub mint    : Pubkey,
+    pub receiver: Pubkey,
+    pub amount  : u64,
+}
+
+#[event]
+pub struct ProtocolTokenFeePaidEvent {
+    pub mint    : Pubkey,
+    pub receiver: Pubkey,
+    pub bps     : u16,
+    pub amount  : u64,
+}
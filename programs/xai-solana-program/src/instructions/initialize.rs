@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::fee::FeeRecommendReward;
+use anchor_spl::token_interface::Mint;
+use crate::error::SwapError;
+use crate::fee::{Fees, MAX_REFERRAL_DEPTH};
+use crate::new_token::CreatorProfile;
 
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
+pub const MAX_FEE_RECEIVERS: usize = 5;
 
 #[account]
 #[derive(Debug, Default, InitSpace)]
@@ -14,6 +18,35 @@ pub struct ProgramSystemAccount {
 #[account]
 #[derive(Debug, Default, InitSpace)]
 pub struct FeeConfig {
+    pub trade_fee_numerator  : u64,
+    pub trade_fee_denominator: u64,
+    pub creation_fee         : u64,
+    pub fee_receiver_account : Pubkey,
+    /// Referral reward per level in bps of the trade fee, the first
+    /// `recommend_award_depth` entries are used
+    pub recommend_award_list : [u16; MAX_REFERRAL_DEPTH],
+    pub recommend_award_depth: u8,
+    /// Launch fee schedule: the trade fee starts at `launch_fee_numerator` when the
+    /// curve is created and decays to `trade_fee_numerator` over `launch_fee_period`
+    /// seconds, linearly or in `launch_fee_steps` equal steps
+    pub launch_fee_numerator : u64,
+    pub launch_fee_period    : i64,
+    pub launch_fee_steps     : u16,
+    /// Share of every trade fee accrued to the mint's `CreatorVault`
+    pub creator_fee_bps      : u16,
+    /// Split of swept protocol fees, the first `fee_receiver_count` entries sum to 10000 bps
+    pub fee_receivers        : [FeeReceiver; MAX_FEE_RECEIVERS],
+    pub fee_receiver_count   : u8,
+    /// Max share of the trade fee a buy/sell can route to its integrator
+    pub max_integrator_bps   : u16,
+    /// Seconds after the last claim before unclaimed referral rewards can be swept, 0 to never expire
+    pub reward_claim_window  : i64,
+}
+
+/// `FeeConfig` as first deployed. `recommend_award_list` has grown since, which
+/// moved the fields after it, so it is rewritten rather than extended.
+#[derive(AnchorDeserialize, Debug, Default)]
+pub struct FeeConfigV0 {
     pub trade_fee_numerator  : u64,
     pub trade_fee_denominator: u64,
     pub creation_fee         : u64,
@@ -21,6 +54,131 @@ pub struct FeeConfig {
     pub recommend_award_list : [u16; 5],
 }
 
+impl FeeConfigV0 {
+    pub const SPACE: usize = ANCHOR_DISCRIMINATOR + 8 + 8 + 8 + 32 + 2 * 5;
+}
+
+/// The settings added since migrate as 0: no launch fee, no creator fee, no
+/// integrator fee, unexpiring rewards and `fee_receiver_account` as the only receiver.
+impl From<FeeConfigV0> for FeeConfig {
+    fn from(fee_config: FeeConfigV0) -> Self {
+        let mut recommend_award_list = [0; MAX_REFERRAL_DEPTH];
+        recommend_award_list[..fee_config.recommend_award_list.len()]
+            .copy_from_slice(&fee_config.recommend_award_list);
+
+        // trailing levels without a reward are not paid, so traders need not pass them
+        let recommend_award_depth = fee_config.recommend_award_list
+            .iter()
+            .rposition(|bps| *bps > 0)
+            .map_or(0, |level| level + 1);
+
+        FeeConfig {
+            trade_fee_numerator  : fee_config.trade_fee_numerator,
+            trade_fee_denominator: fee_config.trade_fee_denominator,
+            creation_fee         : fee_config.creation_fee,
+            fee_receiver_account : fee_config.fee_receiver_account,
+            recommend_award_list,
+            recommend_award_depth: recommend_award_depth.try_into().unwrap(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace, PartialEq)]
+pub struct FeeReceiver {
+    pub receiver: Pubkey,
+    pub bps     : u16,
+}
+
+impl FeeConfig {
+    /// Trade fee numerator in effect `now` for a curve created at `created_at`.
+    pub fn trade_fee_numerator_at(&self, created_at: i64, now: i64) -> u64 {
+        let base = self.trade_fee_numerator;
+        if self.launch_fee_period <= 0 || self.launch_fee_numerator <= base {
+            return base;
+        }
+
+        let elapsed = now.saturating_sub(created_at).max(0);
+        if elapsed >= self.launch_fee_period {
+            return base;
+        }
+
+        let period      = u128::try_from(self.launch_fee_period).unwrap();
+        let mut elapsed = u128::try_from(elapsed).unwrap();
+        if self.launch_fee_steps > 0 {
+            let steps = u128::from(self.launch_fee_steps);
+            elapsed = elapsed * steps / period * period / steps;
+        }
+
+        let extra   = u128::from(self.launch_fee_numerator - base);
+        let decayed = extra.checked_mul(elapsed).unwrap() / period;
+
+        self.launch_fee_numerator - u64::try_from(decayed).unwrap()
+    }
+
+    /// Creator share of `trading_fee`, taken from what is left after referral rewards.
+    pub fn creator_fee(&self, trading_fee: u64, residue_fee: u64) -> u64 {
+        trading_fee
+            .checked_mul(self.creator_fee_bps.into()).unwrap()
+            .checked_div(10000).unwrap()
+            .min(residue_fee)
+    }
+
+    /// Referral reward tiers, closest recommender first.
+    pub fn recommend_award_tiers(&self) -> &[u16] {
+        &self.recommend_award_list[..usize::from(self.recommend_award_depth)]
+    }
+
+    /// Integrator share of `trading_fee`, carved from the protocol's part of the fee.
+    pub fn integrator_fee(&self, trading_fee: u64, integrator_fee_bps: u16, protocol_fee: u64) -> Result<u64> {
+        require!(integrator_fee_bps <= self.max_integrator_bps, SwapError::InvalidIntegratorFee);
+
+        Ok(trading_fee
+            .checked_mul(integrator_fee_bps.into()).unwrap()
+            .checked_div(10000).unwrap()
+            .min(protocol_fee))
+    }
+
+    /// Receivers of swept protocol fees. `fee_receiver_account` takes it all until a split is set.
+    pub fn fee_receivers(&self) -> Vec<FeeReceiver> {
+        if self.fee_receiver_count == 0 {
+            return vec![FeeReceiver {
+                receiver: self.fee_receiver_account,
+                bps     : 10000,
+            }];
+        }
+
+        self.fee_receivers[..usize::from(self.fee_receiver_count)].to_vec()
+    }
+
+    /// Split `amount` by the receivers' bps, the last receiver takes the rounding remainder.
+    pub fn fee_shares(&self, amount: u64) -> Vec<(FeeReceiver, u64)> {
+        let fee_receivers = self.fee_receivers();
+        let mut distributed: u64 = 0;
+
+        fee_receivers.iter().enumerate().map(|(i, fee_receiver)| {
+            let share = if i == fee_receivers.len() - 1 {
+                amount.checked_sub(distributed).unwrap()
+            } else {
+                amount
+                    .checked_mul(fee_receiver.bps.into()).unwrap()
+                    .checked_div(10000).unwrap()
+            };
+            distributed = distributed.checked_add(share).unwrap();
+
+            (*fee_receiver, share)
+        }).collect()
+    }
+
+    pub fn fees_at(&self, created_at: i64, now: i64) -> Fees {
+        Fees {
+            trade_fee_numerator  : self.trade_fee_numerator_at(created_at, now),
+            trade_fee_denominator: self.trade_fee_denominator,
+            fee_receiver_account : self.fee_receiver_account,
+        }
+    }
+}
+
 #[account]
 #[derive(Debug, Default, InitSpace)]
 pub struct InitTokenConfig {
@@ -30,6 +188,21 @@ pub struct InitTokenConfig {
     pub token_total_supply        : u128,
     pub token_max_supply          : u128,
     pub sol_aim                   : u128,
+    pub max_creator_allocation    : u64,
+    /// Default opening window, in the curve's clock unit (seconds or slots)
+    pub opening_window            : u64,
+    /// Default max SOL per wallet during the opening window
+    pub opening_max_buy_lamports  : u64,
+    /// Bounds for per-token overrides
+    pub max_opening_window        : u64,
+    pub opening_max_buy_floor     : u64,
+    /// Default max share of `token_max_supply` one wallet may hold before graduation, 0 for no cap
+    pub max_wallet_bps            : u16,
+}
+
+impl InitTokenConfig {
+    /// Size as first deployed, up to `sol_aim`. The appended settings migrate as 0, i.e. disabled.
+    pub const LEGACY_SPACE: usize = ANCHOR_DISCRIMINATOR + 16 + 16 + 8 + 16 + 16 + 16;
 }
 
 #[account]
@@ -43,6 +216,46 @@ pub struct CurveConfig {
     pub sol_aim              : u128,
     pub k                    : u128,
     pub graduated            : bool,
+    pub creator              : Pubkey,
+    pub created_at           : i64,
+    /// Unix timestamp, or slot if `trading_start_is_slot`
+    pub trading_starts_at    : u64,
+    pub trading_start_is_slot: bool,
+    /// Per-wallet buy cap applies from `trading_starts_at` for `opening_window`
+    pub opening_window       : u64,
+    pub opening_max_buy      : u64,
+    /// Until `allowlist_ends_at`, only buyers proven against `allowlist_root` can buy
+    pub allowlist_root       : [u8; 32],
+    pub allowlist_ends_at    : u64,
+    /// Max share of `token_max_supply` one wallet may hold until graduation, 0 for no cap
+    pub max_wallet_bps       : u16,
+}
+
+impl CurveConfig {
+    /// Size as first deployed, up to `graduated`. Migrated curves get their `creator`
+    /// from the admin and keep `created_at` at 0, so no launch fee applies.
+    pub const LEGACY_SPACE: usize = ANCHOR_DISCRIMINATOR + 7 * 16 + 1;
+
+    /// Current time in the unit `trading_starts_at` is expressed in.
+    pub fn launch_clock(&self, clock: &Clock) -> u64 {
+        if self.trading_start_is_slot {
+            clock.slot
+        } else {
+            clock.unix_timestamp.try_into().unwrap()
+        }
+    }
+
+    pub fn trading_started(&self, clock: &Clock) -> bool {
+        self.launch_clock(clock) >= self.trading_starts_at
+    }
+
+    pub fn in_allowlist_phase(&self, clock: &Clock) -> bool {
+        self.launch_clock(clock) < self.allowlist_ends_at
+    }
+
+    pub fn in_opening_window(&self, clock: &Clock) -> bool {
+        self.launch_clock(clock) < self.trading_starts_at.checked_add(self.opening_window).unwrap()
+    }
 }
 
 ///   Initialize
@@ -126,14 +339,13 @@ pub struct ProgramInitialize2<'info> {
 
 #[derive(Accounts)]
 pub struct InitializeFeeRecommendReward<'info> {
+    /// CHECK: created or migrated by `FeeRecommendReward::migrate`
     #[account(
-        init_if_needed,
+        mut,
         seeds = [b"fee_recommend_reward", recommender.key().as_ref()],
         bump,
-        payer = user,
-        space = ANCHOR_DISCRIMINATOR + FeeRecommendReward::INIT_SPACE,
     )]
-    pub fee_recommend_reward: Account<'info, FeeRecommendReward>,
+    pub fee_recommend_reward: UncheckedAccount<'info>,
 
     /// CHECK: Only used in seeds
     pub recommender: UncheckedAccount<'info>,
@@ -146,7 +358,13 @@ pub struct InitializeFeeRecommendReward<'info> {
 #[account]
 #[derive(Debug, Default, InitSpace)]
 pub struct ProgramConfig {
-    pub version: [u8; 8],
+    pub version              : [u8; 8],
+    pub legacy_launch_enabled: bool,
+}
+
+impl ProgramConfig {
+    /// Size as first deployed, before `legacy_launch_enabled`
+    pub const LEGACY_SPACE: usize = ANCHOR_DISCRIMINATOR + 8;
 }
 
 /// Set owner
@@ -220,3 +438,306 @@ pub struct SetFeeReceiverAccount<'info> {
     pub owner: Signer<'info>,
 }
 
+/// Set fee config
+#[derive(Accounts)]
+pub struct SetFeeConfig<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+}
+
+/// Set program config
+#[derive(Accounts)]
+pub struct SetProgramConfig<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+}
+
+/// Grow `program_config` to the current layout, see `migrate_appended`
+#[derive(Accounts)]
+pub struct MigrateProgramConfig<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    /// CHECK: may still have its legacy layout
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump,
+    )]
+    pub program_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Rewrite `fee_config` from its first deployed layout, see `migrate_rewritten`
+#[derive(Accounts)]
+pub struct MigrateFeeConfig<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    /// CHECK: may still have its legacy layout
+    #[account(
+        mut,
+        seeds = [b"fee_config"],
+        bump,
+    )]
+    pub fee_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow `init_token_config` to the current layout, see `migrate_appended`
+#[derive(Accounts)]
+pub struct MigrateInitTokenConfig<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    /// CHECK: may still have its legacy layout
+    #[account(
+        mut,
+        seeds = [b"init_token_config"],
+        bump,
+    )]
+    pub init_token_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a launched token's `curve_config` to the current layout and record its creator
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct MigrateCurveConfig<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    /// CHECK: may still have its legacy layout
+    #[account(
+        mut,
+        seeds = [b"curve_config", token_mint.key().as_ref()],
+        bump,
+    )]
+    pub curve_config: UncheckedAccount<'info>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// Buys and sells of the token credit the creator's profile
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_profile", creator.as_ref()],
+        bump,
+        payer = admin,
+        space = ANCHOR_DISCRIMINATOR + CreatorProfile::INIT_SPACE,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(
+        mut,
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Set init token config
+#[derive(Accounts)]
+pub struct SetInitTokenConfig<'info> {
+    #[account(
+        seeds = [b"program_system_account"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub program_system_account: Account<'info, ProgramSystemAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"init_token_config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub init_token_config: Account<'info, InitTokenConfig>,
+
+    #[account(
+        constraint = admin.key() == program_system_account.admin
+    )]
+    pub admin: Signer<'info>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_config(launch_fee_period: i64, launch_fee_steps: u16) -> FeeConfig {
+        FeeConfig {
+            trade_fee_numerator  : 100,
+            launch_fee_numerator : 1000,
+            launch_fee_period,
+            launch_fee_steps,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn launch_fee_starts_at_the_launch_numerator() {
+        let fee_config = fee_config(100, 0);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1000), 1000);
+        // clock behind the curve's creation
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 900), 1000);
+    }
+
+    #[test]
+    fn launch_fee_decays_linearly() {
+        let fee_config = fee_config(100, 0);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1050), 550);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1099), 109);
+    }
+
+    #[test]
+    fn launch_fee_ends_at_the_base_fee() {
+        let fee_config = fee_config(100, 0);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1100), 100);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, i64::MAX), 100);
+    }
+
+    #[test]
+    fn launch_fee_decays_in_steps() {
+        let fee_config = fee_config(100, 4);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1024), 1000);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1030), 775);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1099), 325);
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1100), 100);
+    }
+
+    #[test]
+    fn no_launch_fee_without_a_schedule() {
+        assert_eq!(fee_config(0, 0).trade_fee_numerator_at(1000, 1000), 100);
+        assert_eq!(fee_config(-1, 0).trade_fee_numerator_at(1000, 1000), 100);
+
+        let mut fee_config = fee_config(100, 0);
+        fee_config.launch_fee_numerator = 50;
+        assert_eq!(fee_config.trade_fee_numerator_at(1000, 1000), 100);
+    }
+
+    #[test]
+    fn fee_shares_give_the_remainder_to_the_last_receiver() {
+        let mut fee_config = fee_config(0, 0);
+        fee_config.fee_receivers[0] = FeeReceiver { receiver: Pubkey::new_unique(), bps: 3333 };
+        fee_config.fee_receivers[1] = FeeReceiver { receiver: Pubkey::new_unique(), bps: 6667 };
+        fee_config.fee_receiver_count = 2;
+
+        let shares: Vec<u64> = fee_config.fee_shares(100).iter().map(|(_, share)| *share).collect();
+        assert_eq!(shares, vec![33, 67]);
+    }
+
+    #[test]
+    fn fee_shares_default_to_the_fee_receiver_account() {
+        let mut fee_config = fee_config(0, 0);
+        fee_config.fee_receiver_account = Pubkey::new_unique();
+
+        let shares = fee_config.fee_shares(100);
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].0.receiver, fee_config.fee_receiver_account);
+        assert_eq!(shares[0].1, 100);
+    }
+
+    #[test]
+    fn fee_config_v0_keeps_its_award_list() {
+        let fee_config_v0 = FeeConfigV0 {
+            trade_fee_numerator  : 100,
+            trade_fee_denominator: 10000,
+            creation_fee         : 20000000,
+            fee_receiver_account : Pubkey::new_unique(),
+            recommend_award_list : [2000, 1000, 0, 0, 0],
+        };
+        let fee_receiver_account = fee_config_v0.fee_receiver_account;
+
+        let fee_config = FeeConfig::from(fee_config_v0);
+        assert_eq!(fee_config.trade_fee_numerator, 100);
+        assert_eq!(fee_config.trade_fee_denominator, 10000);
+        assert_eq!(fee_config.creation_fee, 20000000);
+        assert_eq!(fee_config.fee_receiver_account, fee_receiver_account);
+        assert_eq!(fee_config.recommend_award_tiers(), &[2000, 1000]);
+        assert_eq!(fee_config.recommend_award_list[2..], [0; MAX_REFERRAL_DEPTH - 2]);
+    }
+
+    #[test]
+    fn fee_config_v0_reads_the_deployed_layout() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&10000u64.to_le_bytes());
+        data.extend_from_slice(&20000000u64.to_le_bytes());
+        data.extend_from_slice(&[7; 32]);
+        for bps in [2000u16, 1000, 0, 0, 500] {
+            data.extend_from_slice(&bps.to_le_bytes());
+        }
+        assert_eq!(ANCHOR_DISCRIMINATOR + data.len(), FeeConfigV0::SPACE);
+
+        let fee_config = FeeConfig::from(FeeConfigV0::deserialize(&mut &data[..]).unwrap());
+        assert_eq!(fee_config.fee_receiver_account, Pubkey::new_from_array([7; 32]));
+        assert_eq!(fee_config.recommend_award_tiers(), &[2000, 1000, 0, 0, 500]);
+    }
+}
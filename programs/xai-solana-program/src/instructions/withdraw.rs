@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 use crate::ProgramSystemAccount;
 
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -15,8 +15,9 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = token_mint,
         associated_token::authority = program_signer,
+        associated_token::token_program = token_program,
     )]
-    pub vault_token: Account<'info, TokenAccount>,
+    pub vault_token: InterfaceAccount<'info, TokenAccount>,
 
     /// CHECK: receive SOL
     #[account(
@@ -26,7 +27,7 @@ pub struct Withdraw<'info> {
     )]
     pub vault_sol: UncheckedAccount<'info>,
 
-    pub token_mint: Account<'info, Mint>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         seeds = [b"program_system_account"],
@@ -46,11 +47,12 @@ pub struct Withdraw<'info> {
         payer = migration,
         associated_token::mint = token_mint,
         associated_token::authority = migration,
+        associated_token::token_program = token_program,
         constraint = migration.key() == migration_ata.owner,
     )]
-    pub migration_ata: Account<'info, TokenAccount>,
+    pub migration_ata: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
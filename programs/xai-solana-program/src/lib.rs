@@ -1,22 +1,31 @@
+pub mod airdrop;
 pub mod bonding_curve;
 pub mod common;
 pub mod error;
+pub mod events;
 pub mod fee;
 pub mod instructions;
 pub mod new_token;
 pub mod swap;
+pub mod vesting;
 
 use crate::{
     fee::*,
     error::SwapError
 };
-use crate::common::{Sol, SplToken};
+use crate::common::{
+    load_account, log_compute_units, migrate_appended, migrate_rewritten, store_account, verify_merkle_proof,
+    Sol, SplToken,
+};
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface;
+use airdrop::*;
 use bonding_curve::*;
+use events::*;
 use instructions::*;
 use new_token::*;
-use serde_json::json;
 use swap::*;
+use vesting::*;
 
 declare_id!("65tLehMbGRJUYJDNP5V2nCy3oVRBQW315gtLxuCSJ88b");
 
@@ -33,7 +42,8 @@ pub mod contract_solana_xai {
         ctx.accounts.fee_config.trade_fee_denominator = trade_fee_denominator;
         ctx.accounts.fee_config.creation_fee          = 20000000; // 0.02 SOL
         ctx.accounts.fee_config.fee_receiver_account  = fee_receiver_account.key();
-        ctx.accounts.fee_config.recommend_award_list  = [2000, 1000, 0 , 0, 0];
+        ctx.accounts.fee_config.recommend_award_list[..2].copy_from_slice(&[2000, 1000]);
+        ctx.accounts.fee_config.recommend_award_depth = 2;
 
         ctx.accounts.program_system_account.owner = ctx.accounts.owner.key();
         ctx.accounts.program_system_account.admin = ctx.accounts.owner.key();
@@ -59,128 +69,567 @@ pub mod contract_solana_xai {
         Ok(())
     }
     
-    pub fn init_create_token_account(_ctx: Context<InitCreateTokenAccount>, params: InitTokenParams) -> Result<()> {
+    pub fn set_legacy_launch(ctx: Context<SetProgramConfig>, enabled: bool) -> Result<()> {
+        ctx.accounts.program_config.legacy_launch_enabled = enabled;
+
         Ok(())
     }
 
-    pub fn create_token(ctx: Context<InitToken>, params: InitTokenParams) -> Result<()> {
-        let identifier = ctx.accounts.identifier_account.key().to_string();
+    pub fn migrate_program_config(ctx: Context<MigrateProgramConfig>) -> Result<()> {
+        migrate_appended::<ProgramConfig>(
+            &ctx.accounts.program_config,
+            ProgramConfig::LEGACY_SPACE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )
+    }
 
-        msg!("create_token identifier: {:?}", identifier);
+    pub fn migrate_fee_config(ctx: Context<MigrateFeeConfig>) -> Result<()> {
+        migrate_rewritten::<FeeConfig, FeeConfigV0>(
+            &ctx.accounts.fee_config,
+            FeeConfigV0::SPACE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )
+    }
 
-        // send creation fee
-        let sol = Sol;
+    pub fn migrate_init_token_config(ctx: Context<MigrateInitTokenConfig>) -> Result<()> {
+        migrate_appended::<InitTokenConfig>(
+            &ctx.accounts.init_token_config,
+            InitTokenConfig::LEGACY_SPACE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )
+    }
 
-        sol.transfer_from(
-            &ctx.accounts.user,
-            &ctx.accounts.fee_receiver_account,
-            ctx.accounts.fee_config.creation_fee,
-            &ctx.accounts.system_program
+    pub fn migrate_curve_config(ctx: Context<MigrateCurveConfig>, creator: Pubkey) -> Result<()> {
+        migrate_appended::<CurveConfig>(
+            &ctx.accounts.curve_config,
+            CurveConfig::LEGACY_SPACE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
         )?;
 
-        // create token
-        let init_virtual_token_reserve: u128 = ctx.accounts.init_token_config.init_virtual_token_reserve;
-        let init_virtual_sol_reserve   = ctx.accounts.init_token_config.init_virtual_sol_reserve;
-        let mint_amount                = ctx.accounts.init_token_config.mint_amount;
-
-        ctx.accounts.curve_config.virtual_token_reserve = init_virtual_token_reserve;
-        ctx.accounts.curve_config.virtual_sol_reserve   = init_virtual_sol_reserve;
-        ctx.accounts.curve_config.token_reserve         = ctx.accounts.init_token_config.token_max_supply;
-        ctx.accounts.curve_config.token_max_supply      = ctx.accounts.init_token_config.token_max_supply;
-        ctx.accounts.curve_config.sol_aim               = ctx.accounts.init_token_config.sol_aim;
-        ctx.accounts.curve_config.k                     = init_virtual_token_reserve
-            .checked_mul(init_virtual_sol_reserve)
-            .unwrap();
-
-        init_token(&ctx, params).unwrap();
-        mint_tokens(&ctx, mint_amount).unwrap();
-
-        // drop_mint_authority(&ctx, &identifier).unwrap();
-
-        let create_token_event = CreateTokenEvent {
-            id  : identifier.clone(),
-            u   : ctx.accounts.user.key().to_string(),
-            mint: ctx.accounts.mint.key().to_string(),
-            aa  : 0,
-            ms  : ctx.accounts.init_token_config.token_max_supply,
-            ts  : mint_amount,
-            rs  : ctx.accounts.init_token_config.init_virtual_sol_reserve,
-            rt  : ctx.accounts.init_token_config.init_virtual_token_reserve,
-        };
+        let mut curve_config: CurveConfig = load_account(&ctx.accounts.curve_config)?;
+        require!(
+            curve_config.creator == Pubkey::default() || curve_config.creator == creator,
+            SwapError::CreatorAlreadySet
+        );
+        curve_config.creator = creator;
+        store_account(&ctx.accounts.curve_config, &curve_config)?;
 
-        msg!("$CreateTokenEvent: {}", json!(create_token_event));
+        ctx.accounts.creator_profile.creator = creator;
 
         Ok(())
     }
 
-    pub fn initialize_fee_recommend_reward(_ctx: Context<InitializeFeeRecommendReward>) -> Result<()> {
+    pub fn set_max_creator_allocation(ctx: Context<SetInitTokenConfig>, max_creator_allocation: u64) -> Result<()> {
+        ctx.accounts.init_token_config.max_creator_allocation = max_creator_allocation;
+
         Ok(())
     }
 
-    pub fn buy(ctx: Context<BuyToken>, amount_in: u128, amount_out_min: u128) -> Result<()> {
-        msg!("buy amount_in: {}", amount_in);
-        require!(amount_in > 0, SwapError::InvalidAmountIn);
+    pub fn set_anti_sniper_config(
+        ctx                     : Context<SetInitTokenConfig>,
+        opening_window          : u64,
+        opening_max_buy_lamports: u64,
+        max_opening_window      : u64,
+        opening_max_buy_floor   : u64,
+    ) -> Result<()> {
+        require!(
+            opening_window <= max_opening_window
+            && opening_max_buy_lamports >= opening_max_buy_floor,
+            SwapError::InvalidOpeningLimits
+        );
+
+        let init_token_config = &mut ctx.accounts.init_token_config;
+        init_token_config.opening_window           = opening_window;
+        init_token_config.opening_max_buy_lamports = opening_max_buy_lamports;
+        init_token_config.max_opening_window       = max_opening_window;
+        init_token_config.opening_max_buy_floor    = opening_max_buy_floor;
+
+        Ok(())
+    }
 
-        let curve_config = &mut ctx.accounts.curve_config;
+    pub fn set_launch_fee_schedule(
+        ctx                 : Context<SetFeeConfig>,
+        launch_fee_numerator: u64,
+        launch_fee_period   : i64,
+        launch_fee_steps    : u16,
+    ) -> Result<()> {
         require!(
-            curve_config.token_reserve > 0 
-            && curve_config.sol_aim > 0 
-            && curve_config.graduated == false, 
-            SwapError::TokenGraduated
+            launch_fee_numerator < ctx.accounts.fee_config.trade_fee_denominator
+            && launch_fee_period >= 0,
+            SwapError::InvalidLaunchFeeSchedule
         );
 
-        let fees = Fees {
-            trade_fee_numerator  : ctx.accounts.fee_config.trade_fee_numerator,
-            trade_fee_denominator: ctx.accounts.fee_config.trade_fee_denominator,
-            fee_receiver_account : ctx.accounts.fee_config.fee_receiver_account,
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.launch_fee_numerator = launch_fee_numerator;
+        fee_config.launch_fee_period    = launch_fee_period;
+        fee_config.launch_fee_steps     = launch_fee_steps;
+
+        Ok(())
+    }
+
+    pub fn set_creator_fee_bps(ctx: Context<SetFeeConfig>, creator_fee_bps: u16) -> Result<()> {
+        require!(creator_fee_bps <= 10000, SwapError::InvalidCreatorFee);
+        ctx.accounts.fee_config.creator_fee_bps = creator_fee_bps;
+
+        Ok(())
+    }
+
+    pub fn set_reward_claim_window(ctx: Context<SetFeeConfig>, reward_claim_window: i64) -> Result<()> {
+        require!(reward_claim_window >= 0, SwapError::InvalidClaimWindow);
+        ctx.accounts.fee_config.reward_claim_window = reward_claim_window;
+
+        Ok(())
+    }
+
+    pub fn set_max_integrator_bps(ctx: Context<SetFeeConfig>, max_integrator_bps: u16) -> Result<()> {
+        require!(max_integrator_bps <= 10000, SwapError::InvalidIntegratorFee);
+        ctx.accounts.fee_config.max_integrator_bps = max_integrator_bps;
+
+        Ok(())
+    }
+
+    pub fn set_max_wallet_bps(ctx: Context<SetInitTokenConfig>, max_wallet_bps: u16) -> Result<()> {
+        require!(max_wallet_bps <= 10000, SwapError::InvalidMaxWallet);
+        ctx.accounts.init_token_config.max_wallet_bps = max_wallet_bps;
+
+        Ok(())
+    }
+
+    pub fn create_token(ctx: Context<CreateToken>, params: InitTokenParams) -> Result<()> {
+        let rent = ctx.accounts.rent.to_account_info();
+        let launch = LaunchToken {
+            metaplex              : Some(MetaplexAccounts {
+                metadata              : &ctx.accounts.metadata,
+                token_metadata_program: &ctx.accounts.token_metadata_program,
+                rent                  : &rent,
+            }),
+            identifier_account    : &ctx.accounts.identifier_account,
+            mint                  : &ctx.accounts.mint.to_account_info(),
+            curve_config          : &mut ctx.accounts.curve_config,
+            program_signer        : &ctx.accounts.program_signer,
+            fee_config            : &ctx.accounts.fee_config,
+            vault                 : &ctx.accounts.vault.to_account_info(),
+            init_token_config     : &ctx.accounts.init_token_config,
+            recommend_reward_vault: &ctx.accounts.recommend_reward_vault,
+            user_ata              : &ctx.accounts.user_ata.to_account_info(),
+            creator_profile       : &mut ctx.accounts.creator_profile,
+            buyer_position        : &mut ctx.accounts.buyer_position,
+            creator_vault         : &mut ctx.accounts.creator_vault,
+            protocol_fee_vault    : &mut ctx.accounts.protocol_fee_vault,
+            creator_vesting       : ctx.accounts.creator_vesting.as_mut(),
+            vesting_vault         : ctx.accounts.vesting_vault.as_ref().map(|v| v.to_account_info()),
+            airdrop               : ctx.accounts.airdrop.as_mut(),
+            airdrop_vault         : ctx.accounts.airdrop_vault.as_ref().map(|v| v.to_account_info()),
+            user                  : &ctx.accounts.user,
+            system_program        : &ctx.accounts.system_program,
+            token_program         : &ctx.accounts.token_program,
+            event_cpi             : EventCpi::new(ctx.accounts.event_authority.to_account_info(), ctx.bumps.event_authority),
+            mint_bump             : ctx.bumps.mint,
+            program_signer_bump   : ctx.bumps.program_signer,
+        };
+
+        launch.launch(params, ctx.remaining_accounts, ctx.program_id)
+    }
+
+    pub fn create_token_2022(
+        ctx       : Context<CreateToken2022>,
+        params    : InitTokenParams,
+        extensions: Token2022Params,
+    ) -> Result<()> {
+        create_mint_2022(&ctx, &params, &extensions)?;
+
+        let launch = LaunchToken {
+            metaplex              : None,
+            identifier_account    : &ctx.accounts.identifier_account,
+            mint                  : &ctx.accounts.mint,
+            curve_config          : &mut ctx.accounts.curve_config,
+            program_signer        : &ctx.accounts.program_signer,
+            fee_config            : &ctx.accounts.fee_config,
+            vault                 : &ctx.accounts.vault,
+            init_token_config     : &ctx.accounts.init_token_config,
+            recommend_reward_vault: &ctx.accounts.recommend_reward_vault,
+            user_ata              : &ctx.accounts.user_ata,
+            creator_profile       : &mut ctx.accounts.creator_profile,
+            buyer_position        : &mut ctx.accounts.buyer_position,
+            creator_vault         : &mut ctx.accounts.creator_vault,
+            protocol_fee_vault    : &mut ctx.accounts.protocol_fee_vault,
+            creator_vesting       : ctx.accounts.creator_vesting.as_mut(),
+            vesting_vault         : ctx.accounts.vesting_vault.as_ref().map(|v| v.to_account_info()),
+            airdrop               : ctx.accounts.airdrop.as_mut(),
+            airdrop_vault         : ctx.accounts.airdrop_vault.as_ref().map(|v| v.to_account_info()),
+            user                  : &ctx.accounts.user,
+            system_program        : &ctx.accounts.system_program,
+            token_program         : &ctx.accounts.token_program,
+            event_cpi             : EventCpi::new(ctx.accounts.event_authority.to_account_info(), ctx.bumps.event_authority),
+            mint_bump             : ctx.bumps.mint,
+            program_signer_bump   : ctx.bumps.program_signer,
+        };
+
+        launch.launch(params, ctx.remaining_accounts, ctx.program_id)
+    }
+
+    /// Legacy two-step launch, step 1: allocates the accounts only.
+    pub fn init_create_token_account(_ctx: Context<InitCreateTokenAccount>, params: InitTokenParams) -> Result<()> {
+        Ok(())
+    }
+
+    /// Legacy two-step launch, step 2.
+    pub fn create_token_legacy(ctx: Context<InitToken>, params: InitTokenParams) -> Result<()> {
+        let rent = ctx.accounts.rent.to_account_info();
+        let launch = LaunchToken {
+            metaplex              : Some(MetaplexAccounts {
+                metadata              : &ctx.accounts.metadata,
+                token_metadata_program: &ctx.accounts.token_metadata_program,
+                rent                  : &rent,
+            }),
+            identifier_account    : &ctx.accounts.identifier_account,
+            mint                  : &ctx.accounts.mint.to_account_info(),
+            curve_config          : &mut ctx.accounts.curve_config,
+            program_signer        : &ctx.accounts.program_signer,
+            fee_config            : &ctx.accounts.fee_config,
+            vault                 : &ctx.accounts.vault.to_account_info(),
+            init_token_config     : &ctx.accounts.init_token_config,
+            recommend_reward_vault: &ctx.accounts.recommend_reward_vault,
+            user_ata              : &ctx.accounts.user_ata.to_account_info(),
+            creator_profile       : &mut ctx.accounts.creator_profile,
+            buyer_position        : &mut ctx.accounts.buyer_position,
+            creator_vault         : &mut ctx.accounts.creator_vault,
+            protocol_fee_vault    : &mut ctx.accounts.protocol_fee_vault,
+            creator_vesting       : None,
+            vesting_vault         : None,
+            airdrop               : None,
+            airdrop_vault         : None,
+            user                  : &ctx.accounts.user,
+            system_program        : &ctx.accounts.system_program,
+            token_program         : &ctx.accounts.token_program,
+            event_cpi             : EventCpi::new(ctx.accounts.event_authority.to_account_info(), ctx.bumps.event_authority),
+            mint_bump             : ctx.bumps.mint,
+            program_signer_bump   : ctx.bumps.program_signer,
         };
 
-        let trading_fee: u128;
-        let amount_in_without_fee: u128;
-        if amount_in > curve_config.sol_aim {
-            trading_fee = fees.calc_trading_fee_reverse(curve_config.sol_aim).unwrap();
-            amount_in_without_fee = curve_config.sol_aim;
-        } else {
-            trading_fee = fees.calc_trading_fee(amount_in).unwrap();
-            amount_in_without_fee = amount_in.checked_sub(trading_fee).unwrap();
+        launch.launch(params, ctx.remaining_accounts, ctx.program_id)
+    }
+
+    pub fn bind_referrer(ctx: Context<BindReferrer>) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let referrer = ctx.accounts.referrer.key();
+        require!(referrer != user, SwapError::InvalidReferrer);
+
+        // walk the referrer's upline until it ends or is deeper than what is paid out,
+        // binding must not close a cycle
+        let mut upline = referrer;
+        let mut chain_ended = false;
+        for binding_account in ctx.remaining_accounts.iter() {
+            require!(!chain_ended, SwapError::ReferralChainMismatch);
+            match ReferralBinding::load(binding_account, &upline, ctx.program_id)? {
+                Some(binding) => {
+                    require!(binding.referrer != user, SwapError::InvalidReferrer);
+                    upline = binding.referrer;
+                }
+                None => chain_ended = true,
+            }
         }
+        require!(
+            chain_ended || ctx.remaining_accounts.len() >= MAX_REFERRAL_DEPTH,
+            SwapError::ReferralChainMismatch
+        );
+
+        let referral_binding = &mut ctx.accounts.referral_binding;
+        referral_binding.user     = user;
+        referral_binding.referrer = referrer;
+        referral_binding.bound_at = Clock::get()?.unix_timestamp;
+
+        FeeRecommendReward::migrate(
+            &ctx.accounts.referrer_fee_recommend_reward,
+            &referrer,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+        )?;
+
+        emit_cpi!(BindReferrerEvent {
+            user,
+            referrer,
+        });
+
+        Ok(())
+    }
 
-        let (mut amount_out, new_virtual_sol_reserve, mut new_virtual_token_reserve) =
-            calculator::buy(&curve_config, amount_in_without_fee)?;
+    pub fn initialize_referral_stats(ctx: Context<InitializeReferralStats>) -> Result<()> {
+        ctx.accounts.referral_stats.recommender = ctx.accounts.recommender.key();
+        ctx.accounts.referral_stats.mint        = ctx.accounts.mint.key();
 
-        curve_config.sol_aim = curve_config.sol_aim.checked_sub(amount_in_without_fee).unwrap();
-        curve_config.sol_reserve += amount_in_without_fee;
+        Ok(())
+    }
 
-        let mut graduated = false;
-        if curve_config.sol_aim == 0 {
-            amount_out = curve_config.token_reserve;
-            new_virtual_token_reserve = curve_config
-                .virtual_token_reserve
-                .checked_sub(amount_out)
-                .unwrap();
+    /// Also migrates an account created before `last_claim_at`, which the claims cannot read.
+    pub fn initialize_fee_recommend_reward(ctx: Context<InitializeFeeRecommendReward>) -> Result<()> {
+        FeeRecommendReward::migrate(
+            &ctx.accounts.fee_recommend_reward,
+            &ctx.accounts.recommender.key(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+        )
+    }
+
+    pub fn set_recommend_award_list(ctx: Context<SetFeeConfig>, recommend_award_list: Vec<u16>) -> Result<()> {
+        require!(recommend_award_list.len() <= MAX_REFERRAL_DEPTH, SwapError::InvalidRecommendAwardList);
+
+        let total_bps = recommend_award_list
+            .iter()
+            .try_fold(0u16, |total, bps| total.checked_add(*bps))
+            .ok_or(SwapError::InvalidRecommendAwardList)?;
+        require!(total_bps <= 10000, SwapError::InvalidRecommendAwardList);
+
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.recommend_award_list = [0; MAX_REFERRAL_DEPTH];
+        fee_config.recommend_award_list[..recommend_award_list.len()].copy_from_slice(&recommend_award_list);
+        fee_config.recommend_award_depth = recommend_award_list.len().try_into().unwrap();
+
+        Ok(())
+    }
+
+    pub fn set_fee_receivers(ctx: Context<SetFeeConfig>, fee_receivers: Vec<FeeReceiver>) -> Result<()> {
+        require!(
+            !fee_receivers.is_empty() && fee_receivers.len() <= MAX_FEE_RECEIVERS,
+            SwapError::InvalidFeeReceivers
+        );
+
+        let mut total_bps: u16 = 0;
+        for (i, fee_receiver) in fee_receivers.iter().enumerate() {
+            require!(
+                fee_receiver.bps > 0
+                && fee_receivers[..i].iter().all(|other| other.receiver != fee_receiver.receiver),
+                SwapError::InvalidFeeReceivers
+            );
+            total_bps = total_bps.checked_add(fee_receiver.bps).ok_or(SwapError::InvalidFeeReceivers)?;
+        }
+        require!(total_bps == 10000, SwapError::InvalidFeeReceivers);
+
+        let fee_config = &mut ctx.accounts.fee_config;
+        fee_config.fee_receivers = [FeeReceiver::default(); MAX_FEE_RECEIVERS];
+        fee_config.fee_receivers[..fee_receivers.len()].copy_from_slice(&fee_receivers);
+        fee_config.fee_receiver_count = fee_receivers.len().try_into().unwrap();
+
+        Ok(())
+    }
+
+    pub fn initialize_protocol_fee_vault(_ctx: Context<InitializeProtocolFeeVault>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn sweep_protocol_fees(ctx: Context<SweepProtocolFees>, amount: Option<u64>) -> Result<()> {
+        let sweepable = ProtocolFeeVault::sweepable(&ctx.accounts.protocol_fee_vault.to_account_info())?;
+        let amount = amount.unwrap_or(sweepable);
+        require!(amount > 0 && amount <= sweepable, SwapError::InvalidSweepAmount);
+
+        let fee_shares = ctx.accounts.fee_config.fee_shares(amount);
+        require!(ctx.remaining_accounts.len() == fee_shares.len(), SwapError::FeeReceiverMismatch);
+
+        let sol = Sol;
+        for (i, (fee_receiver, share)) in fee_shares.iter().enumerate() {
+            let receiver_account = &ctx.remaining_accounts[i];
+            require!(
+                receiver_account.key() == fee_receiver.receiver && receiver_account.is_writable,
+                SwapError::FeeReceiverMismatch
+            );
+
+            sol.transfer_from_pda(
+                &ctx.accounts.protocol_fee_vault.to_account_info(),
+                receiver_account,
+                *share,
+            )?;
+
+            emit_cpi!(ProtocolFeePaidEvent {
+                receiver: fee_receiver.receiver,
+                bps     : fee_receiver.bps,
+                amount  : *share,
+            });
+        }
+
+        let protocol_fee_vault = &mut ctx.accounts.protocol_fee_vault;
+        protocol_fee_vault.total_swept = protocol_fee_vault.total_swept.checked_add(amount).unwrap();
+
+        emit_cpi!(SweepProtocolFeesEvent {
+            amount,
+            total_accrued: ctx.accounts.protocol_fee_vault.total_accrued,
+            total_swept  : ctx.accounts.protocol_fee_vault.total_swept,
+        });
+
+        Ok(())
+    }
+
+    /// Harvest and withdraw the Token-2022 transfer fees of a mint to the protocol fee vault.
+    pub fn withdraw_transfer_fees(ctx: Context<WithdrawTransferFees>) -> Result<()> {
+        let spl_token = SplToken;
+
+        let program_signer_seeds = &[
+            "program_signer".as_bytes(),
+            &[ctx.bumps.program_signer]
+        ];
+
+        for source in ctx.remaining_accounts {
+            require!(source.is_writable, SwapError::InvalidTransferFeeSource);
+        }
+
+        let amount = spl_token.withdraw_withheld_fees(
+            program_signer_seeds,
+            &ctx.accounts.token_mint.to_account_info(),
+            &ctx.accounts.protocol_fee_vault_ata.to_account_info(),
+            &ctx.accounts.program_signer,
+            ctx.remaining_accounts,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit_cpi!(WithdrawTransferFeesEvent {
+            mint    : ctx.accounts.token_mint.key(),
+            receiver: ctx.accounts.protocol_fee_vault.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Split the protocol fee vault's balance of a mint between `fee_config.fee_receivers`.
+    pub fn sweep_protocol_token_fees(ctx: Context<SweepProtocolTokenFees>, amount: Option<u64>) -> Result<()> {
+        let balance = ctx.accounts.protocol_fee_vault_ata.amount;
+        let amount = amount.unwrap_or(balance);
+        require!(amount > 0 && amount <= balance, SwapError::InvalidSweepAmount);
+
+        let fee_shares = ctx.accounts.fee_config.fee_shares(amount);
+        require!(ctx.remaining_accounts.len() == fee_shares.len(), SwapError::FeeReceiverMismatch);
+
+        let spl_token = SplToken;
+        let protocol_fee_vault_seeds = &[
+            "protocol_fee_vault".as_bytes(),
+            &[ctx.bumps.protocol_fee_vault]
+        ];
 
-            graduated = true;
+        for (i, (fee_receiver, share)) in fee_shares.iter().enumerate() {
+            let receiver_ata = &ctx.remaining_accounts[i];
+            let receiver_token_account = InterfaceAccount::<token_interface::TokenAccount>::try_from(receiver_ata)?;
+            require!(
+                receiver_token_account.owner == fee_receiver.receiver
+                    && receiver_token_account.mint == ctx.accounts.token_mint.key()
+                    && receiver_ata.is_writable,
+                SwapError::FeeReceiverMismatch
+            );
+
+            if *share > 0 {
+                spl_token.transfer_from_pda(
+                    protocol_fee_vault_seeds,
+                    &ctx.accounts.protocol_fee_vault_ata.to_account_info(),
+                    &ctx.accounts.protocol_fee_vault.to_account_info(),
+                    receiver_ata,
+                    &ctx.accounts.token_mint.to_account_info(),
+                    ctx.accounts.token_mint.decimals,
+                    *share,
+                    &ctx.accounts.token_program,
+                )?;
+            }
+
+            emit_cpi!(ProtocolTokenFeePaidEvent {
+                mint    : ctx.accounts.token_mint.key(),
+                receiver: fee_receiver.receiver,
+                bps     : fee_receiver.bps,
+                amount  : *share,
+            });
         }
-        msg!("buy amount_out: {:?}", amount_out);
 
-        require!(amount_out >= amount_out_min, SwapError::InsufficientOutputAmount);
+        Ok(())
+    }
+
+    pub fn buy(
+        ctx               : Context<BuyToken>,
+        amount_in         : u128,
+        amount_out_min    : u128,
+        allowlist_proof   : Option<AllowlistProof>,
+        integrator_fee_bps: u16,
+    ) -> Result<()> {
+        log_compute_units("buy start");
+        require!(ctx.accounts.curve_config.trading_started(&Clock::get()?), SwapError::TradingNotStarted);
+
+        // launch fee decays from the curve's creation
+        let fees = ctx.accounts.fee_config.fees_at(
+            ctx.accounts.curve_config.created_at,
+            Clock::get()?.unix_timestamp,
+        );
 
-        let vsr = curve_config.virtual_sol_reserve;
-        let vtr = curve_config.virtual_token_reserve;
+        let BuyOutcome {
+            trading_fee,
+            amount_in_without_fee,
+            amount_out,
+            virtual_sol_reserve: vsr,
+            virtual_token_reserve: vtr,
+            new_virtual_sol_reserve,
+            new_virtual_token_reserve,
+            graduated,
+        } = process_buy(&mut ctx.accounts.curve_config, &fees, amount_in, amount_out_min)?;
+
+        let clock = Clock::get()?;
+        let amount_paid: u64 = trading_fee.checked_add(amount_in_without_fee).unwrap().try_into().unwrap();
+
+        let buyer_position = &mut ctx.accounts.buyer_position;
+        let first_buy = buyer_position.mint == Pubkey::default();
+        buyer_position.mint  = ctx.accounts.token_mint.key();
+        buyer_position.buyer = ctx.accounts.user.key();
+
+        // allowlisted presale phase
+        if ctx.accounts.curve_config.in_allowlist_phase(&clock) {
+            let allowlist_proof = allowlist_proof.ok_or(SwapError::NotAllowlisted)?;
+            require!(
+                verify_merkle_proof(
+                    &allowlist_proof.proof,
+                    ctx.accounts.curve_config.allowlist_root,
+                    allowlist_proof.leaf(&ctx.accounts.user.key()),
+                ),
+                SwapError::NotAllowlisted
+            );
+
+            buyer_position.allowlist_spent = buyer_position.allowlist_spent
+                .checked_add(amount_paid)
+                .unwrap();
+            require!(
+                allowlist_proof.cap == 0 || buyer_position.allowlist_spent <= allowlist_proof.cap,
+                SwapError::AllowlistCapExceeded
+            );
+        }
+
+        // anti-sniper: cap SOL per wallet during the opening window
+        if ctx.accounts.curve_config.in_opening_window(&clock) {
+            buyer_position.spend_opening(amount_paid, ctx.accounts.curve_config.opening_max_buy)?;
+        }
 
-        curve_config.virtual_sol_reserve    = new_virtual_sol_reserve;
-        curve_config.virtual_token_reserve  = new_virtual_token_reserve;
-        curve_config.token_reserve         -= amount_out;
+        let event_cpi = EventCpi::new(ctx.accounts.event_authority.to_account_info(), ctx.bumps.event_authority);
 
         // send fee
         let sol = Sol;
 
+        // referral rewards in the token are bought on the curve, unless that could graduate it
+        let mut token_rewards: Vec<TokenReward> = Vec::new();
+        let token_rewards_enabled = !graduated && trading_fee < ctx.accounts.curve_config.sol_aim;
+
         let (residue_fee, total_reward_fee) = fees.reward_recommend(
             u64::try_from(trading_fee).unwrap(),
+            &ctx.accounts.user,
+            &ctx.accounts.token_mint.key(),
+            first_buy,
             ctx.remaining_accounts,
-            &ctx.accounts.fee_config.recommend_award_list,
+            ctx.accounts.fee_config.recommend_award_tiers(),
+            if token_rewards_enabled { Some(&mut token_rewards) } else { None },
+            &event_cpi,
+            &ctx.accounts.system_program,
             ctx.program_id,
         )?;
+        require!(
+            token_rewards.is_empty() || ctx.accounts.referral_token_vault.is_some(),
+            SwapError::ReferralTokenVaultMissing
+        );
         
         sol.transfer_from(
             &ctx.accounts.user,
@@ -189,22 +638,66 @@ pub mod contract_solana_xai {
             &ctx.accounts.system_program
         )?;
 
+        // creator share of the fee
+        let creator_fee = ctx.accounts.fee_config.creator_fee(u64::try_from(trading_fee).unwrap(), residue_fee);
+        let creator_vault = &mut ctx.accounts.creator_vault;
+        creator_vault.mint    = ctx.accounts.token_mint.key();
+        creator_vault.creator = ctx.accounts.curve_config.creator;
+        creator_vault.accrue(creator_fee);
+
+        sol.transfer_from(
+            &ctx.accounts.user,
+            &ctx.accounts.creator_vault.to_account_info(),
+            creator_fee,
+            &ctx.accounts.system_program
+        )?;
+
+        event_cpi.emit(&CreatorFeeEvent {
+            mint         : ctx.accounts.token_mint.key(),
+            creator      : ctx.accounts.creator_vault.creator,
+            amount       : creator_fee,
+            unclaimed_sol: ctx.accounts.creator_vault.unclaimed_sol,
+        })?;
+
+        // integrator share, carved from the protocol fee
+        let mut protocol_fee = residue_fee.checked_sub(creator_fee).unwrap();
+        let mut integrator_fee: u64 = 0;
+        match &ctx.accounts.integrator {
+            Some(integrator) => {
+                integrator_fee = ctx.accounts.fee_config.integrator_fee(
+                    u64::try_from(trading_fee).unwrap(),
+                    integrator_fee_bps,
+                    protocol_fee,
+                )?;
+                protocol_fee = protocol_fee.checked_sub(integrator_fee).unwrap();
+
+                sol.transfer_from(
+                    &ctx.accounts.user,
+                    &integrator.to_account_info(),
+                    integrator_fee,
+                    &ctx.accounts.system_program
+                )?;
+            }
+            None => require!(integrator_fee_bps == 0, SwapError::InvalidIntegratorFee),
+        }
+
+        // the rest of the fee accrues in the protocol treasury
+        ctx.accounts.protocol_fee_vault.accrue(protocol_fee);
+
         sol.transfer_from(
             &ctx.accounts.user,
-            &ctx.accounts.fee_receiver_account,
-            residue_fee,
+            &ctx.accounts.protocol_fee_vault.to_account_info(),
+            protocol_fee,
             &ctx.accounts.system_program
         )?;
 
-        // receive SOL to vault_sol PDA
+        // receive SOL to the curve_config PDA
         sol.transfer_from(
             &ctx.accounts.user,
-            &ctx.accounts.vault_sol,
+            &ctx.accounts.curve_config.to_account_info(),
             amount_in_without_fee.try_into().unwrap(),
             &ctx.accounts.system_program
         )?;
-        
-        msg!("receive SOL successfully.");
 
         // transfer token
         let spl_token = SplToken;
@@ -214,79 +707,171 @@ pub mod contract_solana_xai {
             &[ctx.bumps.program_signer]
         ];
 
+        // the curve sells `amount_out`, a Token-2022 transfer fee is paid by the receiver
+        let transfer_fee = spl_token.transfer_fee(
+            &ctx.accounts.token_mint.to_account_info(),
+            amount_out.try_into().unwrap(),
+        )?;
+        require!(
+            amount_out.checked_sub(transfer_fee.into()).unwrap() >= amount_out_min,
+            SwapError::InsufficientOutputAmount
+        );
+
         spl_token.transfer_from_pda(
             program_signer_seeds,
             &ctx.accounts.vault.to_account_info(),
             &ctx.accounts.program_signer,
             &ctx.accounts.receiver_ata.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.token_mint.decimals,
             amount_out.try_into().unwrap(),
             &ctx.accounts.token_program,
         )?;
 
+        // max wallet cap, lifted by the graduating buy
+        let max_wallet_bps = ctx.accounts.curve_config.max_wallet_bps;
+        if max_wallet_bps > 0 && !graduated {
+            ctx.accounts.receiver_ata.reload()?;
+            require!(
+                u128::from(ctx.accounts.receiver_ata.amount).checked_mul(10000).unwrap()
+                    <= ctx.accounts.curve_config.token_max_supply.checked_mul(max_wallet_bps.into()).unwrap(),
+                SwapError::MaxWalletExceeded
+            );
+        }
+
+        if let Some(creator_profile) = &mut ctx.accounts.creator_profile {
+            creator_profile.total_volume = creator_profile.total_volume
+                .checked_add(trading_fee.checked_add(amount_in_without_fee).unwrap())
+                .unwrap();
+        }
+
         let timestamp = Clock::get()?.unix_timestamp;
         let timestamp: u64 = timestamp.try_into().unwrap();
 
-        msg!("$BuyEvent: {}", json!(BuyEvent {
-            u   : ctx.accounts.receiver.key().to_string(),
-            ua  : ctx.accounts.receiver_ata.key().to_string(),
-            mint: ctx.accounts.token_mint.key().to_string(),
-            vsr,
-            vtr,
-            nvsr: new_virtual_sol_reserve,
-            nvtr: new_virtual_token_reserve,
-            f   : trading_fee,
-            i   : trading_fee.checked_add(amount_in_without_fee).unwrap(),
-            o   : amount_out,
-            t   : timestamp,
-        }));
+        event_cpi.emit(&BuyEvent {
+            user                     : ctx.accounts.receiver.key(),
+            user_token_account       : ctx.accounts.receiver_ata.key(),
+            mint                     : ctx.accounts.token_mint.key(),
+            virtual_sol_reserve      : vsr,
+            virtual_token_reserve    : vtr,
+            new_virtual_sol_reserve,
+            new_virtual_token_reserve,
+            fee                      : trading_fee,
+            fee_numerator            : fees.trade_fee_numerator,
+            integrator               : ctx.accounts.integrator.as_ref().map(|integrator| integrator.key()),
+            integrator_fee,
+            amount_in                : trading_fee.checked_add(amount_in_without_fee).unwrap(),
+            amount_out,
+            timestamp,
+        })?;
+
+        // after the trade's event, each reward reports the reserves it leaves
+        if let Some(referral_token_vault) = &ctx.accounts.referral_token_vault {
+            if !token_rewards.is_empty() {
+                sol.transfer_from(
+                    &ctx.accounts.user,
+                    &ctx.accounts.curve_config.to_account_info(),
+                    token_rewards.iter().map(|token_reward| token_reward.sol).sum(),
+                    &ctx.accounts.system_program
+                )?;
+
+                buy_token_rewards(
+                    &token_rewards,
+                    ctx.remaining_accounts,
+                    &mut ctx.accounts.curve_config,
+                    &ctx.accounts.vault.to_account_info(),
+                    &ctx.accounts.program_signer,
+                    ctx.bumps.program_signer,
+                    &referral_token_vault.to_account_info(),
+                    &ctx.accounts.token_mint.to_account_info(),
+                    ctx.accounts.token_mint.decimals,
+                    &ctx.accounts.token_program,
+                    &event_cpi,
+                )?;
+            }
+        }
 
         if graduated {
             ctx.accounts.curve_config.graduated = true;
-            msg!("$TokenGraduatedEvent: {}", json!(TokenGraduated {
-                mint: ctx.accounts.token_mint.key().to_string(),
-            }));
-
+            if let Some(creator_profile) = &mut ctx.accounts.creator_profile {
+                creator_profile.graduated_count += 1;
+            }
+            event_cpi.emit(&TokenGraduatedEvent {
+                mint: ctx.accounts.token_mint.key(),
+            })?;
         }
 
+        log_compute_units("buy end");
+
         Ok(())
     }
 
-    pub fn sell(ctx: Context<SellToken>, amount_in: u128, amount_out_min: u128) -> Result<()> {
+    pub fn sell(
+        ctx               : Context<SellToken>,
+        amount_in         : u128,
+        amount_out_min    : u128,
+        integrator_fee_bps: u16,
+    ) -> Result<()> {
+        log_compute_units("sell start");
         require!(amount_in > 0, SwapError::InvalidAmountIn);
         require!(ctx.accounts.curve_config.token_reserve > 0 && ctx.accounts.curve_config.graduated == false, SwapError::TokenGraduated);
+        require!(ctx.accounts.curve_config.trading_started(&Clock::get()?), SwapError::TradingNotStarted);
+
+        // only what actually reaches the vault after a Token-2022 transfer fee is sold to the curve
+        let spl_token = SplToken;
+        let transfer_fee = spl_token.transfer_fee(
+            &ctx.accounts.token_mint.to_account_info(),
+            amount_in.try_into().unwrap(),
+        )?;
+        let amount_received = amount_in.checked_sub(transfer_fee.into()).unwrap();
+        require!(amount_received > 0, SwapError::InvalidAmountIn);
 
         let (amount_out, new_virtual_sol_reserve, new_virtual_token_reserve) =
-            calculator::sell(&ctx.accounts.curve_config, amount_in)?;
-        msg!("sell amount_out: {:?}", amount_out);
+            calculator::sell(&ctx.accounts.curve_config, amount_received)?;
 
-        let fees = Fees {
-            trade_fee_numerator  : ctx.accounts.fee_config.trade_fee_numerator,
-            trade_fee_denominator: ctx.accounts.fee_config.trade_fee_denominator,
-            fee_receiver_account : ctx.accounts.fee_config.fee_receiver_account,
-        };
+        // launch fee decays from the curve's creation
+        let fees = ctx.accounts.fee_config.fees_at(
+            ctx.accounts.curve_config.created_at,
+            Clock::get()?.unix_timestamp,
+        );
 
         let trading_fee = fees.calc_trading_fee(amount_out).unwrap();
 
         // receive token
-        let spl_token = SplToken;
         spl_token.transfer_from(
             &ctx.accounts.user,
             &ctx.accounts.user_token_ata.to_account_info(),
             &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.token_mint.decimals,
             amount_in.try_into().unwrap(),
             &ctx.accounts.token_program,
         )?;
 
-        msg!("receive token successfully.");
+        let event_cpi = EventCpi::new(ctx.accounts.event_authority.to_account_info(), ctx.bumps.event_authority);
 
         let sol = Sol;
+
+        // referral rewards in the token stay in the curve and are bought back after the sell
+        let mut token_rewards: Vec<TokenReward> = Vec::new();
+
         // send recommend reward
         let (residue_fee, total_reward_fee) = fees.reward_recommend(
             u64::try_from(trading_fee).unwrap(),
+            &ctx.accounts.user,
+            &ctx.accounts.token_mint.key(),
+            false,
             ctx.remaining_accounts,
-            &ctx.accounts.fee_config.recommend_award_list,
+            ctx.accounts.fee_config.recommend_award_tiers(),
+            Some(&mut token_rewards),
+            &event_cpi,
+            &ctx.accounts.system_program,
             ctx.program_id,
         )?;
+        require!(
+            token_rewards.is_empty() || ctx.accounts.referral_token_vault.is_some(),
+            SwapError::ReferralTokenVaultMissing
+        );
 
         sol.transfer_from(
             &ctx.accounts.user,
@@ -295,11 +880,54 @@ pub mod contract_solana_xai {
             &ctx.accounts.system_program
         )?;
 
-        // send fee to developers
+        // creator share of the fee
+        let creator_fee = ctx.accounts.fee_config.creator_fee(u64::try_from(trading_fee).unwrap(), residue_fee);
+        let creator_vault = &mut ctx.accounts.creator_vault;
+        creator_vault.mint    = ctx.accounts.token_mint.key();
+        creator_vault.creator = ctx.accounts.curve_config.creator;
+        creator_vault.accrue(creator_fee);
+
         sol.transfer_from_pda(
-            &ctx.accounts.vault_sol,
-            &ctx.accounts.fee_receiver_account,
-            residue_fee,
+            &ctx.accounts.curve_config.to_account_info(),
+            &ctx.accounts.creator_vault.to_account_info(),
+            creator_fee,
+        )?;
+
+        event_cpi.emit(&CreatorFeeEvent {
+            mint         : ctx.accounts.token_mint.key(),
+            creator      : ctx.accounts.creator_vault.creator,
+            amount       : creator_fee,
+            unclaimed_sol: ctx.accounts.creator_vault.unclaimed_sol,
+        })?;
+
+        // integrator share, carved from the protocol fee
+        let mut protocol_fee = residue_fee.checked_sub(creator_fee).unwrap();
+        let mut integrator_fee: u64 = 0;
+        match &ctx.accounts.integrator {
+            Some(integrator) => {
+                integrator_fee = ctx.accounts.fee_config.integrator_fee(
+                    u64::try_from(trading_fee).unwrap(),
+                    integrator_fee_bps,
+                    protocol_fee,
+                )?;
+                protocol_fee = protocol_fee.checked_sub(integrator_fee).unwrap();
+
+                sol.transfer_from_pda(
+                    &ctx.accounts.curve_config.to_account_info(),
+                    &integrator.to_account_info(),
+                    integrator_fee,
+                )?;
+            }
+            None => require!(integrator_fee_bps == 0, SwapError::InvalidIntegratorFee),
+        }
+
+        // the rest of the fee accrues in the protocol treasury
+        ctx.accounts.protocol_fee_vault.accrue(protocol_fee);
+
+        sol.transfer_from_pda(
+            &ctx.accounts.curve_config.to_account_info(),
+            &ctx.accounts.protocol_fee_vault.to_account_info(),
+            protocol_fee,
         )?;
 
         // send the rest of SOL to user
@@ -307,7 +935,7 @@ pub mod contract_solana_xai {
         require!(amount_out_without_fee >= amount_out_min, SwapError::InsufficientOutputAmount); 
 
         sol.transfer_from_pda(
-            &ctx.accounts.vault_sol,
+            &ctx.accounts.curve_config.to_account_info(),
             &ctx.accounts.receiver,
             amount_out_without_fee.try_into().unwrap(),
         )?;
@@ -318,26 +946,54 @@ pub mod contract_solana_xai {
         // update curve_config
         ctx.accounts.curve_config.virtual_sol_reserve    = new_virtual_sol_reserve;
         ctx.accounts.curve_config.virtual_token_reserve  = new_virtual_token_reserve;
-        ctx.accounts.curve_config.token_reserve         += amount_in;
+        ctx.accounts.curve_config.token_reserve         += amount_received;
         ctx.accounts.curve_config.sol_reserve           -= amount_out;
         ctx.accounts.curve_config.sol_aim               += amount_out;
 
+        if let Some(creator_profile) = &mut ctx.accounts.creator_profile {
+            creator_profile.total_volume = creator_profile.total_volume
+                .checked_add(amount_out)
+                .unwrap();
+        }
+
         let timestamp = Clock::get()?.unix_timestamp;
         let timestamp: u64 = timestamp.try_into().unwrap();
 
-        msg!("$SellEvent: {}", json!(SellEvent {
-            u   : ctx.accounts.user.key().to_string(),
-            ua  : ctx.accounts.user_token_ata.key().to_string(),
-            mint: ctx.accounts.token_mint.key().to_string(),
-            vsr,
-            vtr,
-            nvsr: new_virtual_sol_reserve,
-            nvtr: new_virtual_token_reserve,
-            f   : trading_fee,
-            i   : amount_in,
-            o   : amount_out_without_fee,
-            t   : timestamp,
-        }));
+        event_cpi.emit(&SellEvent {
+            user                     : ctx.accounts.user.key(),
+            user_token_account       : ctx.accounts.user_token_ata.key(),
+            mint                     : ctx.accounts.token_mint.key(),
+            virtual_sol_reserve      : vsr,
+            virtual_token_reserve    : vtr,
+            new_virtual_sol_reserve,
+            new_virtual_token_reserve,
+            fee                      : trading_fee,
+            fee_numerator            : fees.trade_fee_numerator,
+            integrator               : ctx.accounts.integrator.as_ref().map(|integrator| integrator.key()),
+            integrator_fee,
+            amount_in,
+            amount_out               : amount_out_without_fee,
+            timestamp,
+        })?;
+
+        // after the trade's event, each reward reports the reserves it leaves
+        if let Some(referral_token_vault) = &ctx.accounts.referral_token_vault {
+            buy_token_rewards(
+                &token_rewards,
+                ctx.remaining_accounts,
+                &mut ctx.accounts.curve_config,
+                &ctx.accounts.vault.to_account_info(),
+                &ctx.accounts.program_signer,
+                ctx.bumps.program_signer,
+                &referral_token_vault.to_account_info(),
+                &ctx.accounts.token_mint.to_account_info(),
+                ctx.accounts.token_mint.decimals,
+                &ctx.accounts.token_program,
+                &event_cpi,
+            )?;
+        }
+
+        log_compute_units("sell end");
 
         Ok(())
     }
@@ -347,6 +1003,7 @@ pub mod contract_solana_xai {
 
         let claim_amount = ctx.accounts.fee_recommend_reward.unclaimed_sol;
         ctx.accounts.fee_recommend_reward.unclaimed_sol = 0;
+        ctx.accounts.fee_recommend_reward.last_claim_at = Clock::get()?.unix_timestamp;
 
         sol.transfer_from_pda(
             &ctx.accounts.recommend_reward_vault,
@@ -362,6 +1019,306 @@ pub mod contract_solana_xai {
         Ok(())
     }
 
+    pub fn set_recommender_payout(ctx: Context<SetRecommenderPayout>) -> Result<()> {
+        ctx.accounts.recommender_payout.recommender = ctx.accounts.recommender.key();
+        ctx.accounts.recommender_payout.payout      = ctx.accounts.payout.key();
+
+        Ok(())
+    }
+
+    pub fn recommender_claim_sol_to_payout(ctx: Context<RecommenderClaimSolToPayout>) -> Result<()> {
+        let sol = Sol;
+
+        let claim_amount = ctx.accounts.fee_recommend_reward.unclaimed_sol;
+        require!(claim_amount > 0, SwapError::NothingToClaim);
+        ctx.accounts.fee_recommend_reward.unclaimed_sol = 0;
+        ctx.accounts.fee_recommend_reward.last_claim_at = Clock::get()?.unix_timestamp;
+
+        sol.transfer_from_pda(
+            &ctx.accounts.recommend_reward_vault,
+            &ctx.accounts.payout,
+            claim_amount,
+        )?;
+
+        emit_cpi!(RecommenderPayoutClaimEvent {
+            recommender: ctx.accounts.recommender_payout.recommender,
+            payout     : ctx.accounts.payout.key(),
+            claim_amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn batch_recommender_claim_sol(ctx: Context<BatchRecommenderClaimSol>) -> Result<()> {
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 3 == 0,
+            SwapError::InvalidBatchClaim
+        );
+
+        let sol = Sol;
+
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            let (fee_recommend_reward_info, recommender_payout_info, payout) = (&accounts[0], &accounts[1], &accounts[2]);
+
+            let recommender_payout: RecommenderPayout = load_account(recommender_payout_info)?;
+            let recommender = recommender_payout.recommender;
+            require!(
+                recommender_payout_info.key() == Pubkey::find_program_address(
+                    &[b"recommender_payout", recommender.as_ref()],
+                    ctx.program_id,
+                ).0
+                && fee_recommend_reward_info.key() == Pubkey::find_program_address(
+                    &[b"fee_recommend_reward", recommender.as_ref()],
+                    ctx.program_id,
+                ).0
+                && payout.key() == recommender_payout.payout
+                && payout.is_writable,
+                SwapError::InvalidBatchClaim
+            );
+
+            let mut fee_recommend_reward: FeeRecommendReward = load_account(fee_recommend_reward_info)?;
+            let claim_amount = fee_recommend_reward.unclaimed_sol;
+            if claim_amount == 0 {
+                continue;
+            }
+            fee_recommend_reward.unclaimed_sol = 0;
+            fee_recommend_reward.last_claim_at = Clock::get()?.unix_timestamp;
+            store_account(fee_recommend_reward_info, &fee_recommend_reward)?;
+
+            sol.transfer_from_pda(
+                &ctx.accounts.recommend_reward_vault,
+                payout,
+                claim_amount,
+            )?;
+
+            emit_cpi!(RecommenderPayoutClaimEvent {
+                recommender,
+                payout: payout.key(),
+                claim_amount,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn set_referral_reward_in_token(ctx: Context<SetReferralRewardInToken>, reward_in_token: bool) -> Result<()> {
+        let referral_stats = &mut ctx.accounts.referral_stats;
+        referral_stats.recommender     = ctx.accounts.recommender.key();
+        referral_stats.mint            = ctx.accounts.token_mint.key();
+        referral_stats.reward_in_token = reward_in_token;
+
+        Ok(())
+    }
+
+    pub fn claim_referral_tokens(ctx: Context<ClaimReferralTokens>) -> Result<()> {
+        let claim_amount = ctx.accounts.referral_stats.unclaimed_tokens;
+        require!(claim_amount > 0, SwapError::NothingToClaim);
+
+        ctx.accounts.referral_stats.unclaimed_tokens = 0;
+
+        let spl_token = SplToken;
+
+        let referral_token_authority_seeds = &[
+            "referral_token_authority".as_bytes(),
+            &[ctx.bumps.referral_token_authority]
+        ];
+
+        spl_token.transfer_from_pda(
+            referral_token_authority_seeds,
+            &ctx.accounts.referral_token_vault.to_account_info(),
+            &ctx.accounts.referral_token_authority,
+            &ctx.accounts.recommender_ata.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.token_mint.decimals,
+            claim_amount,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit_cpi!(ClaimReferralTokensEvent {
+            recommender : ctx.accounts.recommender.key(),
+            mint        : ctx.accounts.token_mint.key(),
+            claim_amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn sweep_stale_recommend_reward(ctx: Context<SweepStaleRecommendReward>) -> Result<()> {
+        let claim_window = ctx.accounts.fee_config.reward_claim_window;
+        let last_claim_at = ctx.accounts.fee_recommend_reward.last_claim_at;
+        require!(
+            claim_window > 0
+            && Clock::get()?.unix_timestamp > last_claim_at.checked_add(claim_window).unwrap(),
+            SwapError::RecommendRewardNotStale
+        );
+
+        let amount = ctx.accounts.fee_recommend_reward.unclaimed_sol;
+        require!(amount > 0, SwapError::NothingToClaim);
+        ctx.accounts.fee_recommend_reward.unclaimed_sol = 0;
+
+        let sol = Sol;
+        sol.transfer_from_pda(
+            &ctx.accounts.recommend_reward_vault,
+            &ctx.accounts.protocol_fee_vault.to_account_info(),
+            amount,
+        )?;
+        ctx.accounts.protocol_fee_vault.accrue(amount);
+
+        emit_cpi!(StaleRecommendRewardSweptEvent {
+            recommender: ctx.accounts.recommender.key(),
+            amount,
+            last_claim_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn creator_claim(ctx: Context<CreatorClaim>) -> Result<()> {
+        let claim_amount = ctx.accounts.creator_vault.unclaimed_sol;
+        require!(claim_amount > 0, SwapError::NothingToClaim);
+
+        ctx.accounts.creator_vault.unclaimed_sol = 0;
+
+        let sol = Sol;
+        sol.transfer_from_pda(
+            &ctx.accounts.creator_vault.to_account_info(),
+            &ctx.accounts.creator,
+            claim_amount,
+        )?;
+
+        emit_cpi!(CreatorClaimEvent {
+            creator     : ctx.accounts.creator.key(),
+            mint        : ctx.accounts.creator_vault.mint,
+            claim_amount,
+            total_earned: ctx.accounts.creator_vault.total_earned,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        let claim_amount = ctx.accounts.creator_vesting.claimable_amount(now);
+        require!(claim_amount > 0, SwapError::NothingToClaim);
+
+        ctx.accounts.creator_vesting.claimed_amount += claim_amount;
+
+        let spl_token = SplToken;
+
+        let token_mint = ctx.accounts.token_mint.key();
+        let creator_vesting_seeds = &[
+            "creator_vesting".as_bytes(),
+            token_mint.as_ref(),
+            &[ctx.bumps.creator_vesting]
+        ];
+
+        spl_token.transfer_from_pda(
+            creator_vesting_seeds,
+            &ctx.accounts.vesting_vault.to_account_info(),
+            &ctx.accounts.creator_vesting.to_account_info(),
+            &ctx.accounts.creator_ata.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.token_mint.decimals,
+            claim_amount,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit_cpi!(ClaimVestedEvent {
+            creator       : ctx.accounts.creator.key(),
+            mint          : token_mint,
+            claim_amount,
+            claimed_amount: ctx.accounts.creator_vesting.claimed_amount,
+            unvested      : ctx.accounts.creator_vesting.unvested_amount(now),
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_airdrop(ctx: Context<ClaimAirdrop>, index: u32, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now < ctx.accounts.airdrop.expires_at && ctx.accounts.airdrop.recovered == false,
+            SwapError::AirdropExpired
+        );
+        require!(!ctx.accounts.airdrop_bitmap.is_claimed(index), SwapError::AirdropAlreadyClaimed);
+
+        let leaf = Airdrop::leaf(index, &ctx.accounts.claimant.key(), amount);
+        require!(
+            verify_merkle_proof(&proof, ctx.accounts.airdrop.merkle_root, leaf),
+            SwapError::InvalidMerkleProof
+        );
+
+        ctx.accounts.airdrop_bitmap.set_claimed(index);
+        ctx.accounts.airdrop.claimed_amount = ctx.accounts.airdrop.claimed_amount.checked_add(amount).unwrap();
+
+        let spl_token = SplToken;
+
+        let token_mint = ctx.accounts.token_mint.key();
+        let airdrop_seeds = &[
+            "airdrop".as_bytes(),
+            token_mint.as_ref(),
+            &[ctx.bumps.airdrop]
+        ];
+
+        spl_token.transfer_from_pda(
+            airdrop_seeds,
+            &ctx.accounts.airdrop_vault.to_account_info(),
+            &ctx.accounts.airdrop.to_account_info(),
+            &ctx.accounts.claimant_ata.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.token_mint.decimals,
+            amount,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit_cpi!(ClaimAirdropEvent {
+            claimant: ctx.accounts.claimant.key(),
+            mint    : token_mint,
+            index,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn recover_airdrop(ctx: Context<RecoverAirdrop>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= ctx.accounts.airdrop.expires_at, SwapError::AirdropNotExpired);
+        require!(ctx.accounts.airdrop.recovered == false, SwapError::NothingToClaim);
+
+        ctx.accounts.airdrop.recovered = true;
+
+        let amount = ctx.accounts.airdrop_vault.amount;
+
+        let token_mint = ctx.accounts.token_mint.key();
+        let airdrop_seeds = &[
+            "airdrop".as_bytes(),
+            token_mint.as_ref(),
+            &[ctx.bumps.airdrop]
+        ];
+
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::Burn {
+                    mint     : ctx.accounts.token_mint.to_account_info(),
+                    from     : ctx.accounts.airdrop_vault.to_account_info(),
+                    authority: ctx.accounts.airdrop.to_account_info(),
+                },
+                &[&airdrop_seeds[..]],
+            ),
+            amount,
+        )?;
+
+        emit_cpi!(RecoverAirdropEvent {
+            mint: token_mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
     pub fn withdraw(ctx: Context<Withdraw>, withdraw_sol_amount: u64, withdraw_token_amount: u64) -> Result<()> {
         // withdraw SOL
         let sol = Sol;
@@ -384,6 +1341,8 @@ pub mod contract_solana_xai {
             &ctx.accounts.vault_token.to_account_info(),
             &ctx.accounts.program_signer,
             &ctx.accounts.migration_ata.to_account_info(),
+            &ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.token_mint.decimals,
             withdraw_token_amount,
             &ctx.accounts.token_program,
         )?;
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+/// Per-creator launch statistics, updated on every launch and trade.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct CreatorProfile {
+    pub creator        : Pubkey,
+    pub tokens_launched: u64,
+    pub total_volume   : u128,
+    pub graduated_count: u64,
+}
+
+impl CreatorProfile {
+    /// Identifier account `creator`'s next launch must use, so mint addresses
+    /// cannot be squatted: PDA of `[b"creator_identifier", creator, tokens_launched]`.
+    pub fn next_identifier(&self, creator: &Pubkey, program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"creator_identifier",
+                creator.as_ref(),
+                &self.tokens_launched.to_le_bytes(),
+            ],
+            program_id,
+        ).0
+    }
+}
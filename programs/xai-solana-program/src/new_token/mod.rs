@@ -1,3 +1,5 @@
+pub mod creator_profile;
 pub mod new_token;
 
-pub use new_token::*;
\ No newline at end of file
+pub use creator_profile::*;
+pub use new_token::*;
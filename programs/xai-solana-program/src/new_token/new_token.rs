@@ -1,125 +1,1025 @@
 use anchor_lang::prelude::Accounts;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::system_program;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{self, AssociatedToken},
     metadata::{
         create_metadata_accounts_v3, mpl_token_metadata::types::DataV2, CreateMetadataAccountsV3,
         Metadata as Metaplex,
     },
-    token::{self, mint_to, Mint, Token, TokenAccount},
+    token::{self, Mint, Token, TokenAccount},
+    token_2022::Token2022,
+    token_interface,
 };
 use spl_token::instruction::AuthorityType;
+use spl_token_2022::extension::{metadata_pointer, transfer_fee, ExtensionType};
+use spl_token_metadata_interface::state::TokenMetadata;
 
-use crate::instructions::*;
-use serde::Serialize;
+use crate::{
+    common::{log_compute_units, Sol, SplToken},
+    error::SwapError,
+    events::*,
+    fee::{CreatorVault, ProtocolFeeVault},
+    instructions::*,
+    swap::*,
+    vesting::CreatorVesting,
+    airdrop::Airdrop,
+};
 
-pub fn init_token(ctx: &Context<InitToken>, metadata: InitTokenParams) -> Result<()> {
-    let identifier_account = ctx.accounts.identifier_account.key();
-    let mint_seeds = &[
-        "mint".as_bytes(),
-        identifier_account.as_ref(),
-        &[ctx.bumps.mint]
-    ];
-    let program_signer_seeds = &[
-        "program_signer".as_bytes(),
-        &[ctx.bumps.program_signer]
-    ];
-    let signer = [&mint_seeds[..], &program_signer_seeds[..]];
-
-    let token_data: DataV2 = DataV2 {
-        name                   : metadata.name,
-        symbol                 : metadata.symbol,
-        uri                    : metadata.uri,
-        seller_fee_basis_points: 0,
-        creators               : None,
-        collection             : None,
-        uses                   : None,
-    };
+pub const TOKEN_DECIMALS: u8 = 9;
 
-    let metadata_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_metadata_program.to_account_info(),
-        CreateMetadataAccountsV3 {
-            payer           : ctx.accounts.user.to_account_info(),
-            update_authority: ctx.accounts.program_signer.to_account_info(),
-            mint            : ctx.accounts.mint.to_account_info(),
-            metadata        : ctx.accounts.metadata.to_account_info(),
-            mint_authority  : ctx.accounts.mint.to_account_info(),
-            system_program  : ctx.accounts.system_program.to_account_info(),
-            rent            : ctx.accounts.rent.to_account_info(),
-        },
-        &signer,
-    );
+/// Metaplex accounts, only used when the mint is owned by the classic token program.
+pub struct MetaplexAccounts<'a, 'info> {
+    pub metadata              : &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub rent                  : &'a AccountInfo<'info>,
+}
 
-    create_metadata_accounts_v3(metadata_ctx, token_data, false, true, None)?;
+/// Accounts shared by the atomic `create_token`, `create_token_2022` and the
+/// legacy two-step `create_token_legacy`, so all of them run exactly the same
+/// launch logic.
+pub struct LaunchToken<'a, 'info> {
+    pub metaplex              : Option<MetaplexAccounts<'a, 'info>>,
+    pub identifier_account    : &'a AccountInfo<'info>,
+    pub mint                  : &'a AccountInfo<'info>,
+    pub curve_config          : &'a mut Account<'info, CurveConfig>,
+    pub program_signer        : &'a AccountInfo<'info>,
+    pub fee_config            : &'a Account<'info, FeeConfig>,
+    pub vault                 : &'a AccountInfo<'info>,
+    pub init_token_config     : &'a Account<'info, InitTokenConfig>,
+    pub recommend_reward_vault: &'a AccountInfo<'info>,
+    pub user_ata              : &'a AccountInfo<'info>,
+    pub creator_profile       : &'a mut Account<'info, CreatorProfile>,
+    pub buyer_position        : &'a mut Account<'info, BuyerPosition>,
+    pub creator_vault         : &'a mut Account<'info, CreatorVault>,
+    pub protocol_fee_vault    : &'a mut Account<'info, ProtocolFeeVault>,
+    pub creator_vesting       : Option<&'a mut Account<'info, CreatorVesting>>,
+    pub vesting_vault         : Option<AccountInfo<'info>>,
+    pub airdrop               : Option<&'a mut Account<'info, Airdrop>>,
+    pub airdrop_vault         : Option<AccountInfo<'info>>,
+    pub user                  : &'a Signer<'info>,
+    pub system_program        : &'a Program<'info, System>,
+    pub token_program         : &'a AccountInfo<'info>,
+    pub event_cpi             : EventCpi<'info>,
+    pub mint_bump             : u8,
+    pub program_signer_bump   : u8,
+}
 
-    msg!("Token mint created successfully.");
+impl<'a, 'info> LaunchToken<'a, 'info> {
+    pub fn launch(
+        mut self,
+        params            : InitTokenParams,
+        remaining_accounts: &[AccountInfo<'info>],
+        program_id        : &Pubkey,
+    ) -> Result<()> {
+        log_compute_units("create_token start");
 
-    Ok(())
+        // creation fee goes to the protocol treasury, split with the trade fees on sweep
+        let sol = Sol;
+
+        self.protocol_fee_vault.accrue(self.fee_config.creation_fee);
+
+        sol.transfer_from(
+            self.user,
+            &self.protocol_fee_vault.to_account_info(),
+            self.fee_config.creation_fee,
+            self.system_program
+        )?;
+
+        // create token
+        let init_virtual_token_reserve: u128 = self.init_token_config.init_virtual_token_reserve;
+        let init_virtual_sol_reserve   = self.init_token_config.init_virtual_sol_reserve;
+        let mint_amount                = self.init_token_config.mint_amount;
+
+        self.curve_config.virtual_token_reserve = init_virtual_token_reserve;
+        self.curve_config.virtual_sol_reserve   = init_virtual_sol_reserve;
+        self.curve_config.token_reserve         = self.init_token_config.token_max_supply;
+        self.curve_config.token_max_supply      = self.init_token_config.token_max_supply;
+        self.curve_config.sol_aim               = self.init_token_config.sol_aim;
+        self.curve_config.k                     = init_virtual_token_reserve
+            .checked_mul(init_virtual_sol_reserve)
+            .unwrap();
+        self.curve_config.creator               = self.user.key();
+        self.curve_config.created_at            = Clock::get()?.unix_timestamp;
+        self.curve_config.trading_start_is_slot = params.trading_start_is_slot;
+        self.curve_config.trading_starts_at     = params.trading_starts_at
+            .max(self.curve_config.launch_clock(&Clock::get()?));
+
+        // anti-sniper limits, per-token overrides must stay within the admin bounds
+        let opening_window = params.opening_window
+            .unwrap_or(self.init_token_config.opening_window);
+        let opening_max_buy = params.opening_max_buy_lamports
+            .unwrap_or(self.init_token_config.opening_max_buy_lamports);
+        require!(
+            opening_window <= self.init_token_config.max_opening_window
+            && opening_max_buy >= self.init_token_config.opening_max_buy_floor,
+            SwapError::InvalidOpeningLimits
+        );
+        self.curve_config.opening_window        = opening_window;
+        self.curve_config.opening_max_buy       = opening_max_buy;
+
+        let max_wallet_bps = params.max_wallet_bps
+            .unwrap_or(self.init_token_config.max_wallet_bps);
+        require!(max_wallet_bps <= 10000, SwapError::InvalidMaxWallet);
+        self.curve_config.max_wallet_bps        = max_wallet_bps;
+
+        // allowlisted presale phase right after trading starts
+        if params.allowlist_root != [0u8; 32] {
+            require!(params.allowlist_duration > 0, SwapError::InvalidAllowlist);
+            self.curve_config.allowlist_root    = params.allowlist_root;
+            self.curve_config.allowlist_ends_at = self.curve_config.trading_starts_at
+                .checked_add(params.allowlist_duration)
+                .unwrap();
+        }
+
+        self.creator_profile.creator = self.user.key();
+        require!(
+            self.identifier_account.key() == self.creator_profile.next_identifier(&self.user.key(), program_id),
+            SwapError::InvalidIdentifier
+        );
+        self.creator_profile.tokens_launched = self.creator_profile.tokens_launched.checked_add(1).unwrap();
+
+        self.creator_vault.mint    = self.mint.key();
+        self.creator_vault.creator = self.user.key();
+
+        let initial_buy_lamports = params.initial_buy_lamports;
+        let min_tokens_out       = params.min_tokens_out;
+        let creator_allocation   = params.creator_allocation;
+        let vesting_cliff        = params.vesting_cliff;
+        let vesting_duration     = params.vesting_duration;
+        let airdrop_amount       = params.airdrop_amount;
+        let airdrop_merkle_root  = params.airdrop_merkle_root;
+        let airdrop_duration     = params.airdrop_duration;
+
+        // creator allocation and airdrop both come out of the part of
+        // `mint_amount` that is not sold on the curve
+        let not_on_curve = u128::from(mint_amount)
+            .checked_sub(self.init_token_config.token_max_supply)
+            .unwrap();
+        require!(
+            u128::from(creator_allocation).checked_add(airdrop_amount.into()).unwrap() <= not_on_curve,
+            SwapError::CreatorAllocationTooLarge
+        );
+
+        self.init_token(params).unwrap();
+        self.mint_tokens(mint_amount).unwrap();
+
+        if creator_allocation > 0 {
+            self.lock_creator_allocation(creator_allocation, vesting_cliff, vesting_duration)?;
+        }
+
+        let mut airdrop_reserved: u64 = 0;
+        if airdrop_amount > 0 {
+            airdrop_reserved = self.reserve_airdrop(airdrop_amount, airdrop_merkle_root, airdrop_duration)?;
+        }
+
+        // creator dev-buy, in the same instruction so nobody can trade in between
+        let mut dev_buy_amount_in: u128 = 0;
+        let mut dev_buy_amount_out: u128 = 0;
+        if initial_buy_lamports > 0 {
+            // launch fee at its peak, like any buy in the curve's first second
+            let fees = self.fee_config.fees_at(
+                self.curve_config.created_at,
+                Clock::get()?.unix_timestamp,
+            );
+
+            let BuyOutcome {
+                trading_fee,
+                amount_in_without_fee,
+                amount_out,
+                virtual_sol_reserve: vsr,
+                virtual_token_reserve: vtr,
+                new_virtual_sol_reserve,
+                new_virtual_token_reserve,
+                graduated,
+            } = process_buy(
+                &mut *self.curve_config,
+                &fees,
+                initial_buy_lamports.into(),
+                min_tokens_out,
+            )?;
+
+            let (residue_fee, total_reward_fee) = fees.reward_recommend(
+                u64::try_from(trading_fee).unwrap(),
+                self.user,
+                &self.mint.key(),
+                true,
+                remaining_accounts,
+                self.fee_config.recommend_award_tiers(),
+                None,
+                &self.event_cpi,
+                self.system_program,
+                program_id,
+            )?;
+
+            sol.transfer_from(
+                self.user,
+                self.recommend_reward_vault,
+                total_reward_fee,
+                self.system_program
+            )?;
+
+            let creator_fee = self.fee_config.creator_fee(u64::try_from(trading_fee).unwrap(), residue_fee);
+            self.creator_vault.accrue(creator_fee);
+
+            sol.transfer_from(
+                self.user,
+                &self.creator_vault.to_account_info(),
+                creator_fee,
+                self.system_program
+            )?;
+
+            self.event_cpi.emit(&CreatorFeeEvent {
+                mint         : self.mint.key(),
+                creator      : self.creator_vault.creator,
+                amount       : creator_fee,
+                unclaimed_sol: self.creator_vault.unclaimed_sol,
+            })?;
+
+            let protocol_fee = residue_fee.checked_sub(creator_fee).unwrap();
+            self.protocol_fee_vault.accrue(protocol_fee);
+
+            sol.transfer_from(
+                self.user,
+                &self.protocol_fee_vault.to_account_info(),
+                protocol_fee,
+                self.system_program
+            )?;
+
+            // the curve_config PDA is the SOL vault
+            sol.transfer_from(
+                self.user,
+                &self.curve_config.to_account_info(),
+                amount_in_without_fee.try_into().unwrap(),
+                self.system_program
+            )?;
+
+            let spl_token = SplToken;
+
+            let program_signer_seeds = &[
+                "program_signer".as_bytes(),
+                &[self.program_signer_bump]
+            ];
+
+            let transfer_fee = spl_token.transfer_fee(self.mint, amount_out.try_into().unwrap())?;
+            require!(
+                amount_out.checked_sub(transfer_fee.into()).unwrap() >= min_tokens_out,
+                SwapError::InsufficientOutputAmount
+            );
+
+            spl_token.transfer_from_pda(
+                program_signer_seeds,
+                self.vault,
+                self.program_signer,
+                self.user_ata,
+                self.mint,
+                TOKEN_DECIMALS,
+                amount_out.try_into().unwrap(),
+                self.token_program,
+            )?;
+
+            // same max wallet cap as `buy`, lifted by the graduating buy
+            let max_wallet_bps = self.curve_config.max_wallet_bps;
+            if max_wallet_bps > 0 && !graduated {
+                require!(
+                    u128::from(spl_token.balance(self.user_ata)?).checked_mul(10000).unwrap()
+                        <= self.curve_config.token_max_supply.checked_mul(max_wallet_bps.into()).unwrap(),
+                    SwapError::MaxWalletExceeded
+                );
+            }
+
+            dev_buy_amount_in  = trading_fee.checked_add(amount_in_without_fee).unwrap();
+            dev_buy_amount_out = amount_out;
+
+            // the dev-buy uses up the creator's opening window cap like any other buy
+            self.buyer_position.mint  = self.mint.key();
+            self.buyer_position.buyer = self.user.key();
+            if self.curve_config.in_opening_window(&Clock::get()?) {
+                self.buyer_position.spend_opening(
+                    u64::try_from(dev_buy_amount_in).unwrap(),
+                    self.curve_config.opening_max_buy,
+                )?;
+            }
+
+            self.creator_profile.total_volume = self.creator_profile.total_volume
+                .checked_add(dev_buy_amount_in)
+                .unwrap();
+
+            let timestamp = Clock::get()?.unix_timestamp;
+            let timestamp: u64 = timestamp.try_into().unwrap();
+
+            self.event_cpi.emit(&BuyEvent {
+                user                     : self.user.key(),
+                user_token_account       : self.user_ata.key(),
+                mint                     : self.mint.key(),
+                virtual_sol_reserve      : vsr,
+                virtual_token_reserve    : vtr,
+                new_virtual_sol_reserve,
+                new_virtual_token_reserve,
+                fee                      : trading_fee,
+                fee_numerator            : fees.trade_fee_numerator,
+                integrator               : None,
+                integrator_fee           : 0,
+                amount_in                : dev_buy_amount_in,
+                amount_out,
+                timestamp,
+            })?;
+
+            if graduated {
+                self.curve_config.graduated = true;
+                self.creator_profile.graduated_count += 1;
+                self.event_cpi.emit(&TokenGraduatedEvent {
+                    mint: self.mint.key(),
+                })?;
+            }
+        }
+
+        self.event_cpi.emit(&CreateTokenEvent {
+            identifier                : self.identifier_account.key(),
+            user                      : self.user.key(),
+            mint                      : self.mint.key(),
+            airdrop_amount            : airdrop_reserved,
+            max_supply                : self.init_token_config.token_max_supply,
+            total_supply              : mint_amount,
+            init_virtual_sol_reserve  : self.init_token_config.init_virtual_sol_reserve,
+            init_virtual_token_reserve: self.init_token_config.init_virtual_token_reserve,
+            sol_aim                   : self.init_token_config.sol_aim,
+            dev_buy_amount_in,
+            dev_buy_amount_out,
+            trading_starts_at         : self.curve_config.trading_starts_at,
+            trading_start_is_slot     : self.curve_config.trading_start_is_slot,
+            allowlist_ends_at         : self.curve_config.allowlist_ends_at,
+        })?;
+
+        log_compute_units("create_token end");
+
+        Ok(())
+    }
+
+    /// Move the creator allocation into the vesting vault.
+    fn lock_creator_allocation(&mut self, amount: u64, cliff: i64, duration: i64) -> Result<()> {
+        require!(
+            amount <= self.init_token_config.max_creator_allocation,
+            SwapError::CreatorAllocationTooLarge
+        );
+        require!(duration > 0 && cliff >= 0 && cliff <= duration, SwapError::InvalidVestingSchedule);
+
+        let (creator_vesting, vesting_vault) = match (self.creator_vesting.as_mut(), &self.vesting_vault) {
+            (Some(creator_vesting), Some(vesting_vault)) => (creator_vesting, vesting_vault),
+            _ => return err!(SwapError::CreatorVestingAccountsMissing),
+        };
+
+        let spl_token = SplToken;
+
+        let program_signer_seeds = &[
+            "program_signer".as_bytes(),
+            &[self.program_signer_bump]
+        ];
+
+        spl_token.transfer_from_pda(
+            program_signer_seeds,
+            self.vault,
+            self.program_signer,
+            vesting_vault,
+            self.mint,
+            TOKEN_DECIMALS,
+            amount,
+            self.token_program,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        creator_vesting.creator        = self.user.key();
+        creator_vesting.mint           = self.mint.key();
+        creator_vesting.total_amount   = amount
+            .checked_sub(spl_token.transfer_fee(self.mint, amount)?)
+            .unwrap();
+        creator_vesting.claimed_amount = 0;
+        creator_vesting.start_at       = now;
+        creator_vesting.cliff_at       = now.checked_add(cliff).unwrap();
+        creator_vesting.end_at         = now.checked_add(duration).unwrap();
+
+        Ok(())
+    }
+
+    /// Move the airdrop into the airdrop vault. Returns the amount claimable,
+    /// net of any Token-2022 transfer fee.
+    fn reserve_airdrop(&mut self, amount: u64, merkle_root: [u8; 32], duration: i64) -> Result<u64> {
+        require!(duration > 0, SwapError::InvalidAirdrop);
+
+        let (airdrop, airdrop_vault) = match (self.airdrop.as_mut(), &self.airdrop_vault) {
+            (Some(airdrop), Some(airdrop_vault)) => (airdrop, airdrop_vault),
+            _ => return err!(SwapError::AirdropAccountsMissing),
+        };
+
+        let spl_token = SplToken;
+
+        let program_signer_seeds = &[
+            "program_signer".as_bytes(),
+            &[self.program_signer_bump]
+        ];
+
+        spl_token.transfer_from_pda(
+            program_signer_seeds,
+            self.vault,
+            self.program_signer,
+            airdrop_vault,
+            self.mint,
+            TOKEN_DECIMALS,
+            amount,
+            self.token_program,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        airdrop.mint           = self.mint.key();
+        airdrop.merkle_root    = merkle_root;
+        airdrop.total_amount   = amount
+            .checked_sub(spl_token.transfer_fee(self.mint, amount)?)
+            .unwrap();
+        airdrop.claimed_amount = 0;
+        airdrop.expires_at     = now.checked_add(duration).unwrap();
+        airdrop.recovered      = false;
+
+        Ok(airdrop.total_amount)
+    }
+
+    fn init_token(&self, metadata: InitTokenParams) -> Result<()> {
+        let identifier_account = self.identifier_account.key();
+        let mint_seeds = &[
+            "mint".as_bytes(),
+            identifier_account.as_ref(),
+            &[self.mint_bump]
+        ];
+        let program_signer_seeds = &[
+            "program_signer".as_bytes(),
+            &[self.program_signer_bump]
+        ];
+        let signer = [&mint_seeds[..], &program_signer_seeds[..]];
+
+        let metaplex = match &self.metaplex {
+            Some(metaplex) => metaplex,
+            None => {
+                // Token-2022: metadata lives on the mint, behind the metadata pointer
+                let ix = spl_token_metadata_interface::instruction::initialize(
+                    &self.token_program.key(),
+                    &self.mint.key(),
+                    &self.program_signer.key(),
+                    &self.mint.key(),
+                    &self.mint.key(),
+                    metadata.name,
+                    metadata.symbol,
+                    metadata.uri,
+                );
+
+                invoke_signed(
+                    &ix,
+                    &[
+                        self.mint.to_account_info(),
+                        self.program_signer.to_account_info(),
+                        self.mint.to_account_info(),
+                        self.mint.to_account_info(),
+                    ],
+                    &signer,
+                )?;
+
+                return Ok(());
+            }
+        };
+
+        let token_data: DataV2 = DataV2 {
+            name                   : metadata.name,
+            symbol                 : metadata.symbol,
+            uri                    : metadata.uri,
+            seller_fee_basis_points: 0,
+            creators               : None,
+            collection             : None,
+            uses                   : None,
+        };
+
+        let metadata_ctx = CpiContext::new_with_signer(
+            metaplex.token_metadata_program.to_account_info(),
+            CreateMetadataAccountsV3 {
+                payer           : self.user.to_account_info(),
+                update_authority: self.program_signer.to_account_info(),
+                mint            : self.mint.to_account_info(),
+                metadata        : metaplex.metadata.to_account_info(),
+                mint_authority  : self.mint.to_account_info(),
+                system_program  : self.system_program.to_account_info(),
+                rent            : metaplex.rent.to_account_info(),
+            },
+            &signer,
+        );
+
+        create_metadata_accounts_v3(metadata_ctx, token_data, false, true, None)?;
+
+        Ok(())
+    }
+
+    fn mint_tokens(&self, quantity: u64) -> Result<()> {
+        let identifier_account = self.identifier_account.key();
+        let mint_seeds = &[
+            "mint".as_bytes(),
+            identifier_account.as_ref(),
+            &[self.mint_bump]
+        ];
+        let signer = [&mint_seeds[..]];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token_interface::MintTo {
+                    authority: self.mint.to_account_info(),
+                    to       : self.vault.to_account_info(),
+                    mint     : self.mint.to_account_info(),
+                },
+                &signer,
+            ),
+            quantity,
+        )?;
+
+        Ok(())
+    }
 }
 
-pub fn mint_tokens(ctx: &Context<InitToken>, quantity: u64) -> Result<()> {
-    let identifier_account = ctx.accounts.identifier_account.key();
+pub fn drop_mint_authority(ctx: &Context<InitToken>, identifier: &String) -> Result<()> {
     let mint_seeds = &[
         "mint".as_bytes(),
-        identifier_account.as_ref(),
+        identifier.as_bytes(),
         &[ctx.bumps.mint]
     ];
     let signer = [&mint_seeds[..]];
 
-    mint_to(
+    token::set_authority(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            token::MintTo {
-                authority: ctx.accounts.mint.to_account_info(),
-                to       : ctx.accounts.vault.to_account_info(),
-                mint     : ctx.accounts.mint.to_account_info(),
+            token::SetAuthority {
+                current_authority: ctx.accounts.mint.to_account_info(),
+                account_or_mint  : ctx.accounts.mint.to_account_info(),
             },
             &signer,
         ),
-        quantity,
+        AuthorityType::MintTokens,
+        None
     )?;
 
     Ok(())
 }
 
-pub fn drop_mint_authority(ctx: &Context<InitToken>, identifier: &String) -> Result<()> {
+/// Allocate the Token-2022 mint PDA with the metadata pointer (pointing at the
+/// mint itself) and, if requested, the transfer-fee extension, then create the
+/// vault and creator ATAs. Rent for the on-mint metadata is prepaid so the token
+/// program can realloc when `init_token` writes it.
+pub fn create_mint_2022(
+    ctx       : &Context<CreateToken2022>,
+    params    : &InitTokenParams,
+    extensions: &Token2022Params,
+) -> Result<()> {
+    let identifier_account = ctx.accounts.identifier_account.key();
     let mint_seeds = &[
         "mint".as_bytes(),
-        identifier.as_bytes(),
+        identifier_account.as_ref(),
         &[ctx.bumps.mint]
     ];
     let signer = [&mint_seeds[..]];
 
-    token::set_authority(
+    let mint = &ctx.accounts.mint.to_account_info();
+    let token_program = &ctx.accounts.token_program.key();
+    let program_signer = &ctx.accounts.program_signer.key();
+
+    let mut extension_types = vec![ExtensionType::MetadataPointer];
+    if extensions.transfer_fee_basis_points > 0 {
+        extension_types.push(ExtensionType::TransferFeeConfig);
+    }
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extension_types)?;
+
+    let token_metadata = TokenMetadata {
+        mint  : mint.key(),
+        name  : params.name.clone(),
+        symbol: params.symbol.clone(),
+        uri   : params.uri.clone(),
+        ..Default::default()
+    };
+    let lamports = Rent::get()?.minimum_balance(space + token_metadata.tlv_size_of()?);
+
+    system_program::create_account(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::SetAuthority {
-                current_authority: ctx.accounts.mint.to_account_info(),
-                account_or_mint  : ctx.accounts.mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: ctx.accounts.user.to_account_info(),
+                to  : mint.clone(),
             },
             &signer,
         ),
-        AuthorityType::MintTokens,
-        None
+        lamports,
+        space.try_into().unwrap(),
+        token_program,
+    )?;
+
+    invoke(
+        &metadata_pointer::instruction::initialize(
+            token_program,
+            &mint.key(),
+            Some(*program_signer),
+            Some(mint.key()),
+        )?,
+        &[mint.clone()],
     )?;
 
+    if extensions.transfer_fee_basis_points > 0 {
+        invoke(
+            &transfer_fee::instruction::initialize_transfer_fee_config(
+                token_program,
+                &mint.key(),
+                Some(program_signer),
+                Some(program_signer),
+                extensions.transfer_fee_basis_points,
+                extensions.maximum_transfer_fee,
+            )?,
+            &[mint.clone()],
+        )?;
+    }
+
+    token_interface::initialize_mint2(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::InitializeMint2 { mint: mint.clone() },
+        ),
+        TOKEN_DECIMALS,
+        &mint.key(),
+        None,
+    )?;
+
+    associated_token::create(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer           : ctx.accounts.user.to_account_info(),
+            associated_token: ctx.accounts.vault.to_account_info(),
+            authority       : ctx.accounts.program_signer.to_account_info(),
+            mint            : mint.clone(),
+            system_program  : ctx.accounts.system_program.to_account_info(),
+            token_program   : ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    associated_token::create_idempotent(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer           : ctx.accounts.user.to_account_info(),
+            associated_token: ctx.accounts.user_ata.to_account_info(),
+            authority       : ctx.accounts.user.to_account_info(),
+            mint            : mint.clone(),
+            system_program  : ctx.accounts.system_program.to_account_info(),
+            token_program   : ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    if let (Some(airdrop), Some(airdrop_vault)) = (&ctx.accounts.airdrop, &ctx.accounts.airdrop_vault) {
+        associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer           : ctx.accounts.user.to_account_info(),
+                associated_token: airdrop_vault.to_account_info(),
+                authority       : airdrop.to_account_info(),
+                mint            : mint.clone(),
+                system_program  : ctx.accounts.system_program.to_account_info(),
+                token_program   : ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+    }
+
+    if let (Some(creator_vesting), Some(vesting_vault)) = (&ctx.accounts.creator_vesting, &ctx.accounts.vesting_vault) {
+        associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer           : ctx.accounts.user.to_account_info(),
+                associated_token: vesting_vault.to_account_info(),
+                authority       : creator_vesting.to_account_info(),
+                mint            : mint.clone(),
+                system_program  : ctx.accounts.system_program.to_account_info(),
+                token_program   : ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+    }
+
     Ok(())
 }
 
+/// Single-instruction launch: allocates the mint, `CurveConfig` and vault,
+/// then configures the curve, mints and writes metadata atomically.
+/// Fits in one transaction when the static accounts go through a lookup table.
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(
     params: InitTokenParams,
 )]
-pub struct InitCreateTokenAccount<'info> {
+pub struct CreateToken<'info> {
+    /// CHECK: New Metaplex Account being created
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Safe.
+    pub identifier_account: AccountInfo<'info>,
+
+    #[account(
+        init,
+        seeds = [b"mint", identifier_account.key().as_ref()],
+        bump,
+        payer = user,
+        mint::decimals = TOKEN_DECIMALS,
+        mint::authority = mint,
+    )]
+    /// CHECK: This is a new token mint
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [b"curve_config", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CurveConfig::INIT_SPACE,
+    )]
+    pub curve_config: Account<'info, CurveConfig>,
+
+    /// CHECK: sign to send token or SOL
+    #[account(seeds = [b"program_signer"], bump)]
+    pub program_signer: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"fee_config"],
+        bump,
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = program_signer,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"init_token_config"],
+        bump,
+    )]
+    pub init_token_config: Account<'info, InitTokenConfig>,
+
+    /// CHECK: only used to receive SOL
+    #[account(
+        mut,
+        seeds = [b"recommend_reward_vault"],
+        bump,
+    )]
+    pub recommend_reward_vault: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    /// Only needed with a creator allocation
+    #[account(
+        init,
+        seeds = [b"creator_vesting", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorVesting::INIT_SPACE,
+    )]
+    pub creator_vesting: Option<Account<'info, CreatorVesting>>,
+
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = creator_vesting,
+    )]
+    pub vesting_vault: Option<Account<'info, TokenAccount>>,
+
+    /// Only needed with an airdrop
+    #[account(
+        init,
+        seeds = [b"airdrop", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + Airdrop::INIT_SPACE,
+    )]
+    pub airdrop: Option<Account<'info, Airdrop>>,
+
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = airdrop,
+    )]
+    pub airdrop_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_profile", user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorProfile::INIT_SPACE,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    /// Counts the dev-buy against the creator's opening window cap
+    #[account(
+        init,
+        seeds = [b"buyer_position", mint.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + BuyerPosition::INIT_SPACE,
+    )]
+    pub buyer_position: Account<'info, BuyerPosition>,
+
+    #[account(
+        init,
+        seeds = [b"creator_vault", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorVault::INIT_SPACE,
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_fee_vault"],
+        bump,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metaplex>,
+    pub associated_token_program: Program<'info, AssociatedToken>   
+}
+
+/// Same as `CreateToken`, but the mint is owned by Token-2022. The mint and
+/// the ATAs are created in the handler, since the extensions have to be
+/// initialized before the mint itself.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    params: InitTokenParams,
+)]
+pub struct CreateToken2022<'info> {
     /// CHECK: Safe.
     pub identifier_account: AccountInfo<'info>,
 
+    /// CHECK: created and initialized in `create_mint_2022`
+    #[account(
+        mut,
+        seeds = [b"mint", identifier_account.key().as_ref()],
+        bump,
+    )]
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [b"curve_config", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CurveConfig::INIT_SPACE,
+    )]
+    pub curve_config: Account<'info, CurveConfig>,
+
+    /// CHECK: sign to send token or SOL
+    #[account(seeds = [b"program_signer"], bump)]
+    pub program_signer: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"fee_config"],
+        bump,
+    )]
+    pub fee_config: Account<'info, FeeConfig>,
+
+    /// CHECK: created in `create_mint_2022`, address checked by the ATA program
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"init_token_config"],
+        bump,
+    )]
+    pub init_token_config: Account<'info, InitTokenConfig>,
+
+    /// CHECK: only used to receive SOL
+    #[account(
+        mut,
+        seeds = [b"recommend_reward_vault"],
+        bump,
+    )]
+    pub recommend_reward_vault: AccountInfo<'info>,
+
+    /// CHECK: created in `create_mint_2022`, address checked by the ATA program
+    #[account(mut)]
+    pub user_ata: UncheckedAccount<'info>,
+
+    /// Only needed with a creator allocation
+    #[account(
+        init,
+        seeds = [b"creator_vesting", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorVesting::INIT_SPACE,
+    )]
+    pub creator_vesting: Option<Account<'info, CreatorVesting>>,
+
+    /// CHECK: created in `create_mint_2022`, address checked by the ATA program
+    #[account(mut)]
+    pub vesting_vault: Option<UncheckedAccount<'info>>,
+
+    /// Only needed with an airdrop
+    #[account(
+        init,
+        seeds = [b"airdrop", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + Airdrop::INIT_SPACE,
+    )]
+    pub airdrop: Option<Account<'info, Airdrop>>,
+
+    /// CHECK: created in `create_mint_2022`, address checked by the ATA program
+    #[account(mut)]
+    pub airdrop_vault: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_profile", user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorProfile::INIT_SPACE,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    /// Counts the dev-buy against the creator's opening window cap
+    #[account(
+        init,
+        seeds = [b"buyer_position", mint.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + BuyerPosition::INIT_SPACE,
+    )]
+    pub buyer_position: Account<'info, BuyerPosition>,
+
+    #[account(
+        init,
+        seeds = [b"creator_vault", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorVault::INIT_SPACE,
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_fee_vault"],
+        bump,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>   
+}
+
+/// Legacy two-step launch, step 1. Only usable while
+/// `program_config.legacy_launch_enabled` is set.
+#[derive(Accounts)]
+#[instruction(
+    params: InitTokenParams,
+)]
+pub struct InitCreateTokenAccount<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump,
+        constraint = program_config.legacy_launch_enabled @ SwapError::LegacyLaunchDisabled,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// CHECK: must be the creator's `CreatorProfile::next_identifier`
+    #[account(
+        constraint = identifier_account.key() == creator_profile.next_identifier(&user.key(), &crate::ID)
+            @ SwapError::InvalidIdentifier
+    )]
+    pub identifier_account: AccountInfo<'info>,
+
     #[account(
         init,
         seeds = [b"mint", identifier_account.key().as_ref()],
         bump,
         payer = user,
-        mint::decimals = 9,
+        mint::decimals = TOKEN_DECIMALS,
         mint::authority = mint,
     )]
     /// CHECK: This is a new token mint
@@ -146,6 +1046,15 @@ pub struct InitCreateTokenAccount<'info> {
     )]
     pub vault: Account<'info, TokenAccount>,
 
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_profile", user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorProfile::INIT_SPACE,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
     #[account(mut)]
     pub user: Signer<'info>,
     // pub rent: Sysvar<'info, Rent>,
@@ -155,11 +1064,21 @@ pub struct InitCreateTokenAccount<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>   
 }
 
+/// Legacy two-step launch, step 2. Only usable while
+/// `program_config.legacy_launch_enabled` is set.
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(
     params: InitTokenParams,
 )]
 pub struct InitToken<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump,
+        constraint = program_config.legacy_launch_enabled @ SwapError::LegacyLaunchDisabled,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
     /// CHECK: New Metaplex Account being created
     #[account(mut)]
     pub metadata: UncheckedAccount<'info>,
@@ -191,13 +1110,6 @@ pub struct InitToken<'info> {
     )]
     pub fee_config: Account<'info, FeeConfig>,
 
-    /// CHECK: safe. only used to receive SOL
-    #[account(
-        mut,
-        constraint = fee_receiver_account.key() == fee_config.fee_receiver_account
-    )]
-    pub fee_receiver_account: UncheckedAccount<'info>,
-
     #[account(
         mut,
         associated_token::mint = mint,
@@ -211,6 +1123,57 @@ pub struct InitToken<'info> {
     )]
     pub init_token_config: Account<'info, InitTokenConfig>,
 
+    /// CHECK: only used to receive SOL
+    #[account(
+        mut,
+        seeds = [b"recommend_reward_vault"],
+        bump,
+    )]
+    pub recommend_reward_vault: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_profile", user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorProfile::INIT_SPACE,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    /// Counts the dev-buy against the creator's opening window cap
+    #[account(
+        init,
+        seeds = [b"buyer_position", mint.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + BuyerPosition::INIT_SPACE,
+    )]
+    pub buyer_position: Account<'info, BuyerPosition>,
+
+    #[account(
+        init,
+        seeds = [b"creator_vault", mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorVault::INIT_SPACE,
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_fee_vault"],
+        bump,
+    )]
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
+
     #[account(mut)]
     pub user: Signer<'info>,
     pub rent: Sysvar<'info, Rent>,
@@ -226,17 +1189,41 @@ pub struct InitTokenParams {
     pub name    : String,
     pub symbol  : String,
     pub uri     : String,
-    pub decimals: u8,
+    /// SOL spent on the creator dev-buy, fee included. 0 skips the dev-buy.
+    pub initial_buy_lamports: u64,
+    pub min_tokens_out      : u128,
+    /// Tokens locked for the creator, taken from `mint_amount - token_max_supply`.
+    /// 0 skips the allocation.
+    pub creator_allocation  : u64,
+    /// Seconds after launch before anything unlocks
+    pub vesting_cliff       : i64,
+    /// Seconds after launch until everything is unlocked
+    pub vesting_duration    : i64,
+    /// Tokens reserved for the airdrop, taken from `mint_amount - token_max_supply`.
+    /// 0 skips the airdrop.
+    pub airdrop_amount      : u64,
+    pub airdrop_merkle_root : [u8; 32],
+    /// Seconds after launch until unclaimed tokens can be recovered
+    pub airdrop_duration    : i64,
+    /// Unix timestamp (or slot with `trading_start_is_slot`) before which
+    /// `buy` and `sell` are rejected. 0 opens trading right away; the creator
+    /// dev-buy is not affected.
+    pub trading_starts_at     : u64,
+    pub trading_start_is_slot : bool,
+    /// Overrides of the `InitTokenConfig` anti-sniper defaults
+    pub opening_window          : Option<u64>,
+    pub opening_max_buy_lamports: Option<u64>,
+    /// Merkle root of `keccak(buyer || cap)` leaves, all zero for no allowlist phase
+    pub allowlist_root          : [u8; 32],
+    /// Length of the allowlist phase, in the curve's clock unit
+    pub allowlist_duration      : u64,
+    /// Override of the `InitTokenConfig` max wallet default, in bps of `token_max_supply`
+    pub max_wallet_bps          : Option<u16>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct CreateTokenEvent {
-    pub id  : String,   // identifier
-    pub u   : String,   // user
-    pub mint: String,   // token_mint
-    pub aa  : u64,      // airdrop_amount
-    pub ms  : u128,     // max_supply
-    pub ts  : u64,      // total_supply
-    pub rs  : u128,     // init_virtual_sol_reserve
-    pub rt  : u128,     // init_virtual_token_reserve
-}
\ No newline at end of file
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct Token2022Params {
+    /// 0 leaves out the transfer-fee extension
+    pub transfer_fee_basis_points: u16,
+    pub maximum_transfer_fee     : u64,
+}
@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+use solana_program::keccak;
+
+/// Proof that the buyer is on the allowlist of a token's presale phase.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct AllowlistProof {
+    /// Max SOL the buyer may spend during the phase, 0 for no cap
+    pub cap  : u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl AllowlistProof {
+    /// leaf = keccak(buyer || cap)
+    pub fn leaf(&self, buyer: &Pubkey) -> [u8; 32] {
+        keccak::hashv(&[buyer.as_ref(), &self.cap.to_le_bytes()]).0
+    }
+}
@@ -1,11 +1,16 @@
-use crate::{CurveConfig, FeeConfig};
+use crate::{
+    error::SwapError,
+    BuyerPosition, CreatorProfile, CreatorVault, CurveConfig, FeeConfig,
+    ProtocolFeeVault, ANCHOR_DISCRIMINATOR,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{Mint, Token, TokenAccount};
-use serde::Serialize;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BuyToken<'info> {
+    /// Also holds the curve's SOL
     #[account(
         mut,
         seeds = [b"curve_config", token_mint.key().as_ref()],
@@ -17,20 +22,30 @@ pub struct BuyToken<'info> {
         mut,
         associated_token::mint = token_mint,
         associated_token::authority = program_signer,
+        associated_token::token_program = token_program,
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: receive SOL
+    /// CHECK: sign to send token or SOL
+    #[account(seeds = [b"program_signer"], bump)]
+    pub program_signer: UncheckedAccount<'info>,
+
+    /// Credited with the trade volume when passed
     #[account(
         mut,
-        seeds = [b"curve_config", token_mint.key().as_ref()],
+        seeds = [b"creator_profile", curve_config.creator.as_ref()],
         bump,
     )]
-    pub vault_sol: UncheckedAccount<'info>,
+    pub creator_profile: Option<Account<'info, CreatorProfile>>,
 
-    /// CHECK: sign to send token or SOL
-    #[account(seeds = [b"program_signer"], bump)]
-    pub program_signer: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_vault", token_mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorVault::INIT_SPACE,
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
 
     #[account(
         seeds = [b"fee_config"],
@@ -38,16 +53,39 @@ pub struct BuyToken<'info> {
     )]
     pub fee_config: Account<'info, FeeConfig>,
 
-    /// CHECK: only used to receive SOL
     #[account(
         mut,
-        constraint = fee_receiver_account.key() == fee_config.fee_receiver_account
+        seeds = [b"protocol_fee_vault"],
+        bump,
     )]
-    pub fee_receiver_account: UncheckedAccount<'info>,
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
 
     #[account(mut)]
     pub user: Signer<'info>,
-    pub token_mint: Account<'info, Mint>,
+
+    /// CHECK: third-party frontend or bot paid `integrator_fee_bps` of the fee,
+    /// never the trader so the fee cannot become a rebate
+    #[account(
+        mut,
+        constraint = integrator.key() != user.key() && integrator.key() != receiver.key()
+            @ SwapError::SelfIntegrator
+    )]
+    pub integrator: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: owns the referral token vaults
+    #[account(seeds = [b"referral_token_authority"], bump)]
+    pub referral_token_authority: Option<UncheckedAccount<'info>>,
+
+    /// Required when an upline takes rewards in the token, see `SetReferralRewardInToken`
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = referral_token_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub referral_token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
     /// CHECK: This is the account only used to receive tokens
     pub receiver: UncheckedAccount<'info>,
 
@@ -56,8 +94,18 @@ pub struct BuyToken<'info> {
         payer = user,
         associated_token::mint = token_mint,
         associated_token::authority = receiver,
+        associated_token::token_program = token_program,
+    )]
+    pub receiver_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"buyer_position", token_mint.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + BuyerPosition::INIT_SPACE,
     )]
-    pub receiver_ata: Account<'info, TokenAccount>,
+    pub buyer_position: Account<'info, BuyerPosition>,
 
     /// CHECK: only used to receive SOL
     #[account(
@@ -67,13 +115,15 @@ pub struct BuyToken<'info> {
     )]
     pub recommend_reward_vault: AccountInfo<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SellToken<'info> {
+    /// Also holds the curve's SOL
     #[account(
         mut,
         seeds = [b"curve_config", token_mint.key().as_ref()],
@@ -85,20 +135,30 @@ pub struct SellToken<'info> {
         mut,
         associated_token::mint = token_mint,
         associated_token::authority = program_signer,
+        associated_token::token_program = token_program,
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: send SOL
+    /// CHECK: sign to send token or SOL
+    #[account(seeds = [b"program_signer"], bump)]
+    pub program_signer: UncheckedAccount<'info>,
+
+    /// Credited with the trade volume when passed
     #[account(
         mut,
-        seeds = [b"curve_config", token_mint.key().as_ref()],
+        seeds = [b"creator_profile", curve_config.creator.as_ref()],
         bump,
     )]
-    pub vault_sol: UncheckedAccount<'info>,
+    pub creator_profile: Option<Account<'info, CreatorProfile>>,
 
-    /// CHECK: sign to send token or SOL
-    #[account(seeds = [b"program_signer"], bump)]
-    pub program_signer: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_vault", token_mint.key().as_ref()],
+        bump,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR + CreatorVault::INIT_SPACE,
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
 
     #[account(
         seeds = [b"fee_config"],
@@ -106,21 +166,44 @@ pub struct SellToken<'info> {
     )]
     pub fee_config: Account<'info, FeeConfig>,
 
-    /// CHECK: only used to receive SOL
     #[account(
         mut,
-        constraint = fee_receiver_account.key() == fee_config.fee_receiver_account
+        seeds = [b"protocol_fee_vault"],
+        bump,
     )]
-    pub fee_receiver_account: UncheckedAccount<'info>,
+    pub protocol_fee_vault: Account<'info, ProtocolFeeVault>,
 
     #[account(
         mut,
         constraint = user.key() == user_token_ata.owner,
     )]
-    pub user_token_ata: Account<'info, TokenAccount>,
+    pub user_token_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
-    pub token_mint: Account<'info, Mint>,
+
+    /// CHECK: third-party frontend or bot paid `integrator_fee_bps` of the fee,
+    /// never the trader so the fee cannot become a rebate
+    #[account(
+        mut,
+        constraint = integrator.key() != user.key() && integrator.key() != receiver.key()
+            @ SwapError::SelfIntegrator
+    )]
+    pub integrator: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: owns the referral token vaults
+    #[account(seeds = [b"referral_token_authority"], bump)]
+    pub referral_token_authority: Option<UncheckedAccount<'info>>,
+
+    /// Required when an upline takes rewards in the token, see `SetReferralRewardInToken`
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = referral_token_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub referral_token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: Safe. This account only used to receive SOL
     #[account(mut)]
@@ -134,42 +217,6 @@ pub struct SellToken<'info> {
     )]
     pub recommend_reward_vault: AccountInfo<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
-
-#[derive(Debug, Serialize)]
-pub struct BuyEvent {
-    pub u   : String,   // user                     
-    pub ua  : String,   // user's token account
-    pub mint: String,   // token_mint               
-    pub vsr : u128,     // virtual_sol_reserve  
-    pub vtr : u128,     // virtual_token_reserve
-    pub nvsr: u128,     // new_virtual_sol_reserve  
-    pub nvtr: u128,     // new_virtual_token_reserve
-    pub f   : u128,     // fee
-    pub i   : u128,     // amount_in                
-    pub o   : u128,     // amount_out               
-    pub t   : u64,      // timestamp                
-}
-
-#[derive(Debug, Serialize)]
-pub struct TokenGraduated {
-    pub mint: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct SellEvent {
-    pub u   : String,   // user                     
-    pub ua  : String,   // user's token account
-    pub mint: String,   // token_mint               
-    pub vsr : u128,     // virtual_sol_reserve  
-    pub vtr : u128,     // virtual_token_reserve
-    pub nvsr: u128,     // new_virtual_sol_reserve  
-    pub nvtr: u128,     // new_virtual_token_reserve
-    pub f   : u128,     // fee
-    pub i   : u128,     // amount_in                
-    pub o   : u128,     // amount_out               
-    pub t   : u64,      // timestamp                
-}
\ No newline at end of file
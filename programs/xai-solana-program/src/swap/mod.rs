@@ -1,3 +1,9 @@
+pub mod allowlist;
 pub mod instruction;
+pub mod position;
+pub mod process;
 
-pub use instruction::*;
\ No newline at end of file
+pub use allowlist::*;
+pub use instruction::*;
+pub use position::*;
+pub use process::*;
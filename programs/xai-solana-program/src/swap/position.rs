@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::error::SwapError;
+
+/// Per-(mint, buyer) record of what a wallet bought while launch limits apply.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct BuyerPosition {
+    pub mint           : Pubkey,
+    pub buyer          : Pubkey,
+    /// SOL spent during the opening window, fee included
+    pub opening_spent  : u64,
+    /// SOL spent during the allowlist phase, fee included
+    pub allowlist_spent: u64,
+}
+
+impl BuyerPosition {
+    /// Add `amount` to the opening window spend, capped at `opening_max_buy`.
+    pub fn spend_opening(&mut self, amount: u64, opening_max_buy: u64) -> Result<()> {
+        self.opening_spent = self.opening_spent.checked_add(amount).unwrap();
+        require!(self.opening_spent <= opening_max_buy, SwapError::OpeningBuyCapExceeded);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening_spend_up_to_the_cap() {
+        let mut buyer_position = BuyerPosition::default();
+        buyer_position.spend_opening(60, 100).unwrap();
+        buyer_position.spend_opening(40, 100).unwrap();
+        assert_eq!(buyer_position.opening_spent, 100);
+    }
+
+    #[test]
+    fn opening_spend_over_the_cap_fails() {
+        let mut buyer_position = BuyerPosition::default();
+        buyer_position.spend_opening(60, 100).unwrap();
+        let err: Error = SwapError::OpeningBuyCapExceeded.into();
+        assert_eq!(buyer_position.spend_opening(41, 100).unwrap_err(), err);
+    }
+
+    #[test]
+    fn dev_buy_over_the_cap_fails() {
+        let mut buyer_position = BuyerPosition::default();
+        assert!(buyer_position.spend_opening(101, 100).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use crate::{bonding_curve::*, error::SwapError, fee::Fees, CurveConfig};
+
+#[derive(Clone, Debug, Default)]
+pub struct BuyOutcome {
+    pub trading_fee              : u128,
+    pub amount_in_without_fee    : u128,
+    pub amount_out               : u128,
+    pub virtual_sol_reserve      : u128,
+    pub virtual_token_reserve    : u128,
+    pub new_virtual_sol_reserve  : u128,
+    pub new_virtual_token_reserve: u128,
+    pub graduated                : bool,
+}
+
+/// Run the curve and fee math of a buy and update `curve_config` accordingly.
+/// Moving SOL and tokens is left to the caller.
+pub fn process_buy(
+    curve_config  : &mut Account<CurveConfig>,
+    fees          : &Fees,
+    amount_in     : u128,
+    amount_out_min: u128,
+) -> Result<BuyOutcome> {
+    require!(amount_in > 0, SwapError::InvalidAmountIn);
+    require!(
+        curve_config.token_reserve > 0
+        && curve_config.sol_aim > 0
+        && curve_config.graduated == false,
+        SwapError::TokenGraduated
+    );
+
+    let trading_fee: u128;
+    let amount_in_without_fee: u128;
+    if amount_in > curve_config.sol_aim {
+        trading_fee = fees.calc_trading_fee_reverse(curve_config.sol_aim).unwrap();
+        amount_in_without_fee = curve_config.sol_aim;
+    } else {
+        trading_fee = fees.calc_trading_fee(amount_in).unwrap();
+        amount_in_without_fee = amount_in.checked_sub(trading_fee).unwrap();
+    }
+
+    let (mut amount_out, new_virtual_sol_reserve, mut new_virtual_token_reserve) =
+        calculator::buy(&curve_config, amount_in_without_fee)?;
+
+    curve_config.sol_aim = curve_config.sol_aim.checked_sub(amount_in_without_fee).unwrap();
+    curve_config.sol_reserve += amount_in_without_fee;
+
+    let mut graduated = false;
+    if curve_config.sol_aim == 0 {
+        amount_out = curve_config.token_reserve;
+        new_virtual_token_reserve = curve_config
+            .virtual_token_reserve
+            .checked_sub(amount_out)
+            .unwrap();
+
+        graduated = true;
+    }
+
+    require!(amount_out >= amount_out_min, SwapError::InsufficientOutputAmount);
+
+    let vsr = curve_config.virtual_sol_reserve;
+    let vtr = curve_config.virtual_token_reserve;
+
+    curve_config.virtual_sol_reserve    = new_virtual_sol_reserve;
+    curve_config.virtual_token_reserve  = new_virtual_token_reserve;
+    curve_config.token_reserve         -= amount_out;
+
+    Ok(BuyOutcome {
+        trading_fee,
+        amount_in_without_fee,
+        amount_out,
+        virtual_sol_reserve: vsr,
+        virtual_token_reserve: vtr,
+        new_virtual_sol_reserve,
+        new_virtual_token_reserve,
+        graduated,
+    })
+}
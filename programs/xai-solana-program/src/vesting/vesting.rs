@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Creator allocation locked at launch, unlocked linearly between `start_at`
+/// and `end_at`, nothing before `cliff_at`. Tokens sit in the ATA of this PDA.
+#[account]
+#[derive(Debug, Default, InitSpace)]
+pub struct CreatorVesting {
+    pub creator       : Pubkey,
+    pub mint          : Pubkey,
+    pub total_amount  : u64,
+    pub claimed_amount: u64,
+    pub start_at      : i64,
+    pub cliff_at      : i64,
+    pub end_at        : i64,
+}
+
+impl CreatorVesting {
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if now < self.cliff_at {
+            return 0;
+        }
+        if now >= self.end_at {
+            return self.total_amount;
+        }
+
+        let elapsed  = u128::try_from(now - self.start_at).unwrap();
+        let duration = u128::try_from(self.end_at - self.start_at).unwrap();
+
+        u64::try_from(
+            u128::from(self.total_amount)
+                .checked_mul(elapsed).unwrap()
+                .checked_div(duration).unwrap()
+        ).unwrap()
+    }
+
+    pub fn unvested_amount(&self, now: i64) -> u64 {
+        self.total_amount.checked_sub(self.vested_amount(now)).unwrap()
+    }
+
+    pub fn claimable_amount(&self, now: i64) -> u64 {
+        self.vested_amount(now).checked_sub(self.claimed_amount).unwrap()
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_vesting", token_mint.key().as_ref()],
+        bump,
+        has_one = creator,
+    )]
+    pub creator_vesting: Account<'info, CreatorVesting>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = creator_vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = token_mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program,
+    )]
+    pub creator_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct ClaimVestedEvent {
+    pub creator       : Pubkey,
+    pub mint          : Pubkey,
+    pub claim_amount  : u64,
+    pub claimed_amount: u64,
+    pub unvested      : u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(total_amount: u64, start_at: i64, cliff_at: i64, end_at: i64) -> CreatorVesting {
+        CreatorVesting {
+            total_amount,
+            start_at,
+            cliff_at,
+            end_at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nothing_vests_before_the_cliff() {
+        let vesting = schedule(1_000, 100, 200, 1100);
+        assert_eq!(vesting.vested_amount(0), 0);
+        assert_eq!(vesting.vested_amount(199), 0);
+        assert_eq!(vesting.unvested_amount(199), 1_000);
+    }
+
+    #[test]
+    fn cliff_releases_the_linear_amount_since_start() {
+        let vesting = schedule(1_000, 100, 200, 1100);
+        assert_eq!(vesting.vested_amount(200), 100);
+    }
+
+    #[test]
+    fn vests_linearly_between_cliff_and_end() {
+        let vesting = schedule(1_000, 100, 200, 1100);
+        assert_eq!(vesting.vested_amount(600), 500);
+        assert_eq!(vesting.vested_amount(1099), 999);
+    }
+
+    #[test]
+    fn everything_vests_at_and_after_the_end() {
+        let vesting = schedule(1_000, 100, 200, 1100);
+        assert_eq!(vesting.vested_amount(1100), 1_000);
+        assert_eq!(vesting.vested_amount(i64::MAX), 1_000);
+        assert_eq!(vesting.unvested_amount(1100), 0);
+    }
+
+    #[test]
+    fn claimable_excludes_what_was_claimed() {
+        let mut vesting = schedule(1_000, 100, 200, 1100);
+        vesting.claimed_amount = 300;
+        assert_eq!(vesting.claimable_amount(600), 200);
+    }
+
+    #[test]
+    fn no_overflow_at_the_bounds() {
+        let vesting = schedule(u64::MAX, 0, 0, i64::MAX);
+        assert_eq!(vesting.vested_amount(i64::MAX - 1), u64::MAX - 3);
+        assert_eq!(vesting.vested_amount(i64::MAX), u64::MAX);
+
+        let vesting = schedule(u64::MAX, i64::MIN + 1, i64::MIN + 1, 0);
+        assert_eq!(vesting.vested_amount(-1), u64::MAX - 3);
+    }
+}
@@ -0,0 +1,126 @@
+import { BN } from "@coral-xyz/anchor";
+import { keccak_256 } from "@noble/hashes/sha3";
+import { ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, getMint } from "@solana/spl-token";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  Launch,
+  admin,
+  ata,
+  connection,
+  eventCpi,
+  expectError,
+  fundedKeypair,
+  launchToken2022,
+  pda,
+  program,
+  programSystemAccount,
+  setup,
+  tokenBalance,
+  u64,
+  waitForClock,
+} from "./helpers";
+
+function u32(value: number): Buffer {
+  const buffer = Buffer.alloc(4);
+  buffer.writeUInt32LE(value);
+  return buffer;
+}
+
+/// `Airdrop::leaf`
+function leaf(index: number, claimant: PublicKey, amount: BN): Buffer {
+  return Buffer.from(keccak_256(Buffer.concat([u32(index), claimant.toBuffer(), u64(amount)])));
+}
+
+/// Sorted pair hash of `verify_merkle_proof`
+function hashPair(a: Buffer, b: Buffer): Buffer {
+  const [first, second] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return Buffer.from(keccak_256(Buffer.concat([first, second])));
+}
+
+describe("airdrop", () => {
+  const amounts = [new BN(100_000_000_000), new BN(200_000_000_000)];
+  let claimants: Keypair[];
+  let leaves: Buffer[];
+  let launch: Launch;
+
+  before(async () => {
+    await setup();
+    claimants = [await fundedKeypair(), await fundedKeypair()];
+    leaves = claimants.map((claimant, index) => leaf(index, claimant.publicKey, amounts[index]));
+    launch = await launchToken2022(await fundedKeypair(), {
+      airdropAmount: amounts[0].add(amounts[1]),
+      airdropMerkleRoot: [...hashPair(leaves[0], leaves[1])],
+      airdropDuration: new BN(10),
+    });
+  });
+
+  function claim(index: number, amount = amounts[index]) {
+    const claimant = claimants[index];
+    const airdrop = pda("airdrop", launch.mint);
+    return program.methods
+      .claimAirdrop(index, amount, [[...leaves[1 - index]]])
+      .accounts({
+        airdrop,
+        airdropBitmap: pda("airdrop_bitmap", launch.mint, u32(0)),
+        airdropVault: ata(launch.mint, airdrop),
+        claimantAta: ata(launch.mint, claimant.publicKey),
+        tokenMint: launch.mint,
+        claimant: claimant.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        ...eventCpi,
+      })
+      .signers([claimant])
+      .rpc({ commitment: "confirmed" });
+  }
+
+  function recover(signer = admin) {
+    const airdrop = pda("airdrop", launch.mint);
+    return program.methods
+      .recoverAirdrop()
+      .accounts({
+        airdrop,
+        airdropVault: ata(launch.mint, airdrop),
+        tokenMint: launch.mint,
+        programSystemAccount,
+        admin: signer.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        ...eventCpi,
+      })
+      .signers([signer])
+      .rpc({ commitment: "confirmed" });
+  }
+
+  it("pays a claim once", async () => {
+    await claim(0);
+    expect(await tokenBalance(ata(launch.mint, claimants[0].publicKey))).to.equal(BigInt(amounts[0].toString()));
+
+    await expectError(claim(0), "AirdropAlreadyClaimed");
+  });
+
+  it("rejects a claim outside of the merkle tree", async () => {
+    await expectError(claim(1, amounts[1].addn(1)), "InvalidMerkleProof");
+  });
+
+  it("is only recovered by the admin after expiry", async () => {
+    await expectError(recover(), "AirdropNotExpired");
+    await expectError(recover(await fundedKeypair()), "ConstraintRaw");
+  });
+
+  it("burns what is left after expiry", async () => {
+    const airdrop = await program.account.airdrop.fetch(pda("airdrop", launch.mint));
+    await waitForClock(airdrop.expiresAt);
+    await expectError(claim(1), "AirdropExpired");
+
+    const supply = (await getMint(connection, launch.mint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply;
+    await recover();
+
+    expect(await tokenBalance(ata(launch.mint, pda("airdrop", launch.mint)))).to.equal(0n);
+    const burned = supply - (await getMint(connection, launch.mint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply;
+    expect(burned).to.equal(BigInt(amounts[1].toString()));
+
+    await expectError(recover(), "NothingToClaim");
+  });
+});
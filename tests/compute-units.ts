@@ -0,0 +1,48 @@
+import { LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  ata,
+  buy,
+  computeUnits,
+  fundedKeypair,
+  launchToken,
+  launchToken2022,
+  sell,
+  setup,
+  tokenBalance,
+} from "./helpers";
+
+// Prints the rows of the README's "Compute units" table for the current build.
+// Run once built with `--features compute-units` and once with
+// `--features compute-units,legacy-json-events`.
+describe("compute units", () => {
+  const rows: [string, number][] = [];
+
+  before(setup);
+
+  after(() => {
+    console.log("\n| Instruction | Compute units |\n|---|---|");
+    for (const [instruction, units] of rows) {
+      console.log(`| ${instruction} | ${units} |`);
+    }
+  });
+
+  it("create_token, buy and sell", async () => {
+    const creator = await fundedKeypair();
+    const trader = await fundedKeypair();
+
+    const launch = await launchToken(creator);
+    rows.push(["create_token", await computeUnits(launch.signature)]);
+
+    rows.push(["buy", await computeUnits(await buy(trader, launch, LAMPORTS_PER_SOL))]);
+
+    const tokens = await tokenBalance(ata(launch.mint, trader.publicKey, launch.tokenProgram), launch.tokenProgram);
+    rows.push(["sell", await computeUnits(await sell(trader, launch, tokens / 2n))]);
+  });
+
+  it("create_token_2022", async () => {
+    const creator = await fundedKeypair();
+
+    const launch = await launchToken2022(creator);
+    rows.push(["create_token_2022", await computeUnits(launch.signature)]);
+  });
+});
@@ -0,0 +1,397 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import {
+  AccountMeta,
+  ComputeBudgetProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_RENT_PUBKEY,
+  SystemProgram,
+} from "@solana/web3.js";
+import { expect } from "chai";
+import { XaiSolanaProgram } from "../target/types/xai_solana_program";
+
+export const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+export const program = anchor.workspace.XaiSolanaProgram as Program<XaiSolanaProgram>;
+export const connection = provider.connection;
+export const admin = (provider.wallet as anchor.Wallet).payer;
+
+export const METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// `recommend_award_depth` set by `initialize1`
+export const REFERRAL_DEPTH = 2;
+/// `mint_amount - token_max_supply` set by `initialize2`
+export const NOT_ON_CURVE = new BN("254900000000000000");
+
+export const feeReceiver = Keypair.generate();
+
+export function u64(value: number | BN): Buffer {
+  return new BN(value).toArrayLike(Buffer, "le", 8);
+}
+
+export function pda(...seeds: (string | PublicKey | Buffer)[]): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    seeds.map((seed) =>
+      typeof seed === "string" ? Buffer.from(seed) : seed instanceof PublicKey ? seed.toBuffer() : seed
+    ),
+    program.programId
+  )[0];
+}
+
+export function ata(mint: PublicKey, owner: PublicKey, tokenProgram = TOKEN_2022_PROGRAM_ID): PublicKey {
+  return getAssociatedTokenAddressSync(mint, owner, true, tokenProgram);
+}
+
+export const programSystemAccount = pda("program_system_account");
+export const programSigner = pda("program_signer");
+export const feeConfig = pda("fee_config");
+export const initTokenConfig = pda("init_token_config");
+export const programConfig = pda("program_config");
+export const recommendRewardVault = pda("recommend_reward_vault");
+export const protocolFeeVault = pda("protocol_fee_vault");
+
+export const eventCpi = {
+  eventAuthority: pda("__event_authority"),
+  program: program.programId,
+};
+
+export function sleep(ms: number): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, ms));
+}
+
+/// Wait until the validator's clock reaches `unixTimestamp`.
+export async function waitForClock(unixTimestamp: number | BN): Promise<void> {
+  const target = new BN(unixTimestamp).toNumber();
+  while ((await connection.getBlockTime(await connection.getSlot("confirmed"))) < target) {
+    await sleep(500);
+  }
+}
+
+export async function fund(owner: PublicKey, sol = 10): Promise<void> {
+  const signature = await connection.requestAirdrop(owner, sol * LAMPORTS_PER_SOL);
+  const blockhash = await connection.getLatestBlockhash();
+  await connection.confirmTransaction({ signature, ...blockhash }, "confirmed");
+}
+
+export async function fundedKeypair(sol = 10): Promise<Keypair> {
+  const keypair = Keypair.generate();
+  await fund(keypair.publicKey, sol);
+  return keypair;
+}
+
+export async function tokenBalance(account: PublicKey, tokenProgram = TOKEN_2022_PROGRAM_ID): Promise<bigint> {
+  return (await getAccount(connection, account, "confirmed", tokenProgram)).amount;
+}
+
+/// Compute units used by a confirmed transaction.
+export async function computeUnits(signature: string): Promise<number> {
+  const transaction = await connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  return transaction.meta.computeUnitsConsumed;
+}
+
+/// Expect `promise` to fail with the Anchor error `code`, e.g. `OpeningBuyCapExceeded`.
+export async function expectError(promise: Promise<unknown>, code: string): Promise<void> {
+  try {
+    await promise;
+  } catch (err) {
+    const anchorError = err instanceof anchor.AnchorError ? err : anchor.AnchorError.parse(err.logs ?? []);
+    expect(anchorError?.error.errorCode.code ?? String(err)).to.equal(code);
+    return;
+  }
+  expect.fail(`expected ${code}`);
+}
+
+const computeBudget = ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 });
+
+let initialized: Promise<void> | undefined;
+
+/// Initialize the program once per validator, shared by every test file.
+export function setup(): Promise<void> {
+  if (!initialized) {
+    initialized = initialize();
+  }
+  return initialized;
+}
+
+async function initialize(): Promise<void> {
+  await fund(feeReceiver.publicKey, 1);
+  if (await connection.getAccountInfo(programSystemAccount)) {
+    return;
+  }
+
+  await program.methods
+    .initialize1()
+    .accounts({
+      programSystemAccount,
+      programSigner,
+      feeConfig,
+      migrationAccount: admin.publicKey,
+      feeReceiverAccount: feeReceiver.publicKey,
+      owner: admin.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+
+  await program.methods
+    .initialize2()
+    .accounts({
+      initTokenConfig,
+      programConfig,
+      recommendRewardVault,
+      owner: admin.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+
+  await program.methods
+    .initializeProtocolFeeVault()
+    .accounts({
+      programSystemAccount,
+      protocolFeeVault,
+      admin: admin.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+
+  await program.methods
+    .setMaxCreatorAllocation(NOT_ON_CURVE)
+    .accounts({ programSystemAccount, initTokenConfig, admin: admin.publicKey })
+    .rpc();
+
+  // no opening window by default, launches may set one of up to an hour
+  await program.methods
+    .setAntiSniperConfig(new BN(0), new BN(0), new BN(3600), new BN(0))
+    .accounts({ programSystemAccount, initTokenConfig, admin: admin.publicKey })
+    .rpc();
+}
+
+export function launchParams(overrides: Record<string, unknown> = {}) {
+  return {
+    name: "Test Token",
+    symbol: "TEST",
+    uri: "https://example.com/token.json",
+    initialBuyLamports: new BN(0),
+    minTokensOut: new BN(0),
+    creatorAllocation: new BN(0),
+    vestingCliff: new BN(0),
+    vestingDuration: new BN(0),
+    airdropAmount: new BN(0),
+    airdropMerkleRoot: Array(32).fill(0),
+    airdropDuration: new BN(0),
+    tradingStartsAt: new BN(0),
+    tradingStartIsSlot: false,
+    openingWindow: null,
+    openingMaxBuyLamports: null,
+    allowlistRoot: Array(32).fill(0),
+    allowlistDuration: new BN(0),
+    maxWalletBps: null,
+    ...overrides,
+  };
+}
+
+export interface Launch {
+  creator: Keypair;
+  mint: PublicKey;
+  curveConfig: PublicKey;
+  tokenProgram: PublicKey;
+  signature: string;
+}
+
+/// Referral `remaining_accounts` of a trade by `user`: its upline, closest
+/// first, each with the recommender's stats for `mint` when listed in `stats`.
+export function referralAccounts(
+  user: PublicKey,
+  mint: PublicKey,
+  upline: PublicKey[] = [],
+  stats: PublicKey[] = []
+): AccountMeta[] {
+  const accounts: AccountMeta[] = [];
+  let referee = user;
+  for (const recommender of upline) {
+    accounts.push(
+      { pubkey: pda("referral_binding", referee), isSigner: false, isWritable: false },
+      { pubkey: recommender, isSigner: false, isWritable: false },
+      { pubkey: pda("fee_recommend_reward", recommender), isSigner: false, isWritable: true }
+    );
+    if (stats.some((key) => key.equals(recommender))) {
+      accounts.push({ pubkey: pda("referral_stats", recommender, mint), isSigner: false, isWritable: true });
+    }
+    referee = recommender;
+  }
+  if (upline.length < REFERRAL_DEPTH) {
+    accounts.push({ pubkey: pda("referral_binding", referee), isSigner: false, isWritable: false });
+  }
+  return accounts;
+}
+
+async function nextIdentifier(creator: PublicKey): Promise<PublicKey> {
+  const profile = await program.account.creatorProfile.fetchNullable(pda("creator_profile", creator));
+  return pda("creator_identifier", creator, u64(profile ? profile.tokensLaunched : 0));
+}
+
+function launchAccounts(creator: PublicKey, identifier: PublicKey, params, tokenProgram: PublicKey) {
+  const mint = pda("mint", identifier);
+  const creatorVesting = params.creatorAllocation.gtn(0) ? pda("creator_vesting", mint) : null;
+  const airdrop = params.airdropAmount.gtn(0) ? pda("airdrop", mint) : null;
+
+  return {
+    identifierAccount: identifier,
+    mint,
+    curveConfig: pda("curve_config", mint),
+    programSigner,
+    feeConfig,
+    vault: ata(mint, programSigner, tokenProgram),
+    initTokenConfig,
+    recommendRewardVault,
+    userAta: ata(mint, creator, tokenProgram),
+    creatorVesting,
+    vestingVault: creatorVesting && ata(mint, creatorVesting, tokenProgram),
+    airdrop,
+    airdropVault: airdrop && ata(mint, airdrop, tokenProgram),
+    creatorProfile: pda("creator_profile", creator),
+    buyerPosition: pda("buyer_position", mint, creator),
+    creatorVault: pda("creator_vault", mint),
+    protocolFeeVault,
+    user: creator,
+    systemProgram: SystemProgram.programId,
+    tokenProgram,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    ...eventCpi,
+  };
+}
+
+/// Launch through `create_token_2022`, which needs no Metaplex program.
+export async function launchToken2022(
+  creator: Keypair,
+  overrides: Record<string, unknown> = {},
+  extensions = { transferFeeBasisPoints: 0, maximumTransferFee: new BN(0) }
+): Promise<Launch> {
+  const params = launchParams(overrides);
+  const identifier = await nextIdentifier(creator.publicKey);
+  const accounts = launchAccounts(creator.publicKey, identifier, params, TOKEN_2022_PROGRAM_ID);
+
+  const signature = await program.methods
+    .createToken2022(params, extensions)
+    .accounts(accounts)
+    .remainingAccounts(
+      params.initialBuyLamports.gtn(0) ? referralAccounts(creator.publicKey, accounts.mint) : []
+    )
+    .preInstructions([computeBudget])
+    .signers([creator])
+    .rpc({ commitment: "confirmed" });
+
+  return {
+    creator,
+    mint: accounts.mint,
+    curveConfig: accounts.curveConfig,
+    tokenProgram: TOKEN_2022_PROGRAM_ID,
+    signature,
+  };
+}
+
+/// Launch through `create_token`, a classic mint with Metaplex metadata.
+export async function launchToken(creator: Keypair, overrides: Record<string, unknown> = {}): Promise<Launch> {
+  const params = launchParams(overrides);
+  const identifier = await nextIdentifier(creator.publicKey);
+  const accounts = launchAccounts(creator.publicKey, identifier, params, TOKEN_PROGRAM_ID);
+  const metadata = PublicKey.findProgramAddressSync(
+    [Buffer.from("metadata"), METADATA_PROGRAM_ID.toBuffer(), accounts.mint.toBuffer()],
+    METADATA_PROGRAM_ID
+  )[0];
+
+  const signature = await program.methods
+    .createToken(params)
+    .accounts({
+      ...accounts,
+      metadata,
+      rent: SYSVAR_RENT_PUBKEY,
+      tokenMetadataProgram: METADATA_PROGRAM_ID,
+    })
+    .remainingAccounts(
+      params.initialBuyLamports.gtn(0) ? referralAccounts(creator.publicKey, accounts.mint) : []
+    )
+    .preInstructions([computeBudget])
+    .signers([creator])
+    .rpc({ commitment: "confirmed" });
+
+  return {
+    creator,
+    mint: accounts.mint,
+    curveConfig: accounts.curveConfig,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    signature,
+  };
+}
+
+export interface TradeOptions {
+  amountOutMin?: BN;
+  upline?: PublicKey[];
+  stats?: PublicKey[];
+  referralTokenVault?: boolean;
+}
+
+function tradeAccounts(user: PublicKey, launch: Launch, options: TradeOptions) {
+  const referralTokenAuthority = pda("referral_token_authority");
+  return {
+    curveConfig: launch.curveConfig,
+    vault: ata(launch.mint, programSigner, launch.tokenProgram),
+    programSigner,
+    creatorProfile: pda("creator_profile", launch.creator.publicKey),
+    creatorVault: pda("creator_vault", launch.mint),
+    feeConfig,
+    protocolFeeVault,
+    user,
+    integrator: null,
+    referralTokenAuthority: options.referralTokenVault ? referralTokenAuthority : null,
+    referralTokenVault: options.referralTokenVault
+      ? ata(launch.mint, referralTokenAuthority, launch.tokenProgram)
+      : null,
+    tokenMint: launch.mint,
+    receiver: user,
+    recommendRewardVault,
+    tokenProgram: launch.tokenProgram,
+    systemProgram: SystemProgram.programId,
+    ...eventCpi,
+  };
+}
+
+export async function buy(user: Keypair, launch: Launch, lamports: number | BN, options: TradeOptions = {}) {
+  return program.methods
+    .buy(new BN(lamports), options.amountOutMin ?? new BN(0), null, 0)
+    .accounts({
+      ...tradeAccounts(user.publicKey, launch, options),
+      receiverAta: ata(launch.mint, user.publicKey, launch.tokenProgram),
+      buyerPosition: pda("buyer_position", launch.mint, user.publicKey),
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(referralAccounts(user.publicKey, launch.mint, options.upline, options.stats))
+    .preInstructions([computeBudget])
+    .signers([user])
+    .rpc({ commitment: "confirmed" });
+}
+
+export async function sell(user: Keypair, launch: Launch, tokens: number | BN | bigint, options: TradeOptions = {}) {
+  return program.methods
+    .sell(new BN(tokens.toString()), options.amountOutMin ?? new BN(0), 0)
+    .accounts({
+      ...tradeAccounts(user.publicKey, launch, options),
+      userTokenAta: ata(launch.mint, user.publicKey, launch.tokenProgram),
+    })
+    .remainingAccounts(referralAccounts(user.publicKey, launch.mint, options.upline, options.stats))
+    .preInstructions([computeBudget])
+    .signers([user])
+    .rpc({ commitment: "confirmed" });
+}
@@ -0,0 +1,45 @@
+import { BN } from "@coral-xyz/anchor";
+import { LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { expect } from "chai";
+import { buy, expectError, fundedKeypair, launchToken2022, pda, program, setup, waitForClock } from "./helpers";
+
+const openingCap = { openingWindow: new BN(60), openingMaxBuyLamports: new BN(LAMPORTS_PER_SOL) };
+
+describe("opening window", () => {
+  before(setup);
+
+  it("caps what each wallet spends during the window", async () => {
+    const launch = await launchToken2022(await fundedKeypair(), openingCap);
+    const sniper = await fundedKeypair();
+
+    await buy(sniper, launch, 0.6 * LAMPORTS_PER_SOL);
+    await expectError(buy(sniper, launch, 0.5 * LAMPORTS_PER_SOL), "OpeningBuyCapExceeded");
+
+    const position = await program.account.buyerPosition.fetch(pda("buyer_position", launch.mint, sniper.publicKey));
+    expect(position.openingSpent.toNumber()).to.equal(0.6 * LAMPORTS_PER_SOL);
+
+    await buy(await fundedKeypair(), launch, LAMPORTS_PER_SOL);
+  });
+
+  it("counts the creator's dev-buy against the cap", async () => {
+    await expectError(
+      launchToken2022(await fundedKeypair(), { ...openingCap, initialBuyLamports: new BN(2 * LAMPORTS_PER_SOL) }),
+      "OpeningBuyCapExceeded"
+    );
+  });
+
+  it("keeps per-token limits within the admin bounds", async () => {
+    await expectError(
+      launchToken2022(await fundedKeypair(), { ...openingCap, openingWindow: new BN(3601) }),
+      "InvalidOpeningLimits"
+    );
+  });
+
+  it("lifts the cap once the window is over", async () => {
+    const launch = await launchToken2022(await fundedKeypair(), { ...openingCap, openingWindow: new BN(2) });
+    const curveConfig = await program.account.curveConfig.fetch(launch.curveConfig);
+
+    await waitForClock(curveConfig.tradingStartsAt.add(curveConfig.openingWindow));
+    await buy(await fundedKeypair(), launch, 2 * LAMPORTS_PER_SOL);
+  });
+});
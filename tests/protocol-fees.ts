@@ -0,0 +1,112 @@
+import { BN } from "@coral-xyz/anchor";
+import { AccountMeta, Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  admin,
+  buy,
+  connection,
+  eventCpi,
+  expectError,
+  feeConfig,
+  feeReceiver,
+  fundedKeypair,
+  launchToken2022,
+  program,
+  programSystemAccount,
+  protocolFeeVault,
+  setup,
+} from "./helpers";
+
+function receivers(...keys: PublicKey[]): AccountMeta[] {
+  return keys.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+}
+
+function sweep(amount: BN | null, remainingAccounts = receivers(feeReceiver.publicKey), signer: Keypair = admin) {
+  return program.methods
+    .sweepProtocolFees(amount)
+    .accounts({ programSystemAccount, protocolFeeVault, feeConfig, admin: signer.publicKey, ...eventCpi })
+    .remainingAccounts(remainingAccounts)
+    .signers([signer])
+    .rpc({ commitment: "confirmed" });
+}
+
+async function sweepable(): Promise<number> {
+  const vault = await connection.getAccountInfo(protocolFeeVault, "confirmed");
+  return vault.lamports - (await connection.getMinimumBalanceForRentExemption(vault.data.length));
+}
+
+describe("sweep_protocol_fees", () => {
+  before(async () => {
+    await setup();
+    await buy(await fundedKeypair(), await launchToken2022(await fundedKeypair()), LAMPORTS_PER_SOL);
+  });
+
+  it("accrues trade fees in the vault", async () => {
+    const vault = await program.account.protocolFeeVault.fetch(protocolFeeVault);
+    expect(vault.totalAccrued.toNumber()).to.be.greaterThan(0);
+    expect(await sweepable()).to.be.greaterThan(0);
+  });
+
+  it("pays the fee receiver", async () => {
+    const before = await connection.getBalance(feeReceiver.publicKey, "confirmed");
+
+    await sweep(new BN(1_000));
+
+    expect(await connection.getBalance(feeReceiver.publicKey, "confirmed")).to.equal(before + 1_000);
+  });
+
+  it("rejects more than the vault holds", async () => {
+    await expectError(sweep(new BN((await sweepable()) + 1)), "InvalidSweepAmount");
+  });
+
+  it("rejects other receivers", async () => {
+    await expectError(sweep(new BN(1_000), receivers(Keypair.generate().publicKey)), "FeeReceiverMismatch");
+    await expectError(sweep(new BN(1_000), []), "FeeReceiverMismatch");
+  });
+
+  it("is admin only", async () => {
+    await expectError(sweep(new BN(1_000), undefined, await fundedKeypair()), "ConstraintRaw");
+  });
+});
+
+describe("set_fee_receivers", () => {
+  function setFeeReceivers(feeReceivers: { receiver: PublicKey; bps: number }[]) {
+    return program.methods
+      .setFeeReceivers(feeReceivers)
+      .accounts({ programSystemAccount, feeConfig, admin: admin.publicKey })
+      .rpc({ commitment: "confirmed" });
+  }
+
+  before(setup);
+
+  // later sweeps pay the single default receiver again
+  after(() => setFeeReceivers([{ receiver: feeReceiver.publicKey, bps: 10_000 }]));
+
+  it("rejects splits that do not add up to 10000 bps", async () => {
+    const receiver = Keypair.generate().publicKey;
+
+    await expectError(setFeeReceivers([{ receiver, bps: 9_999 }]), "InvalidFeeReceivers");
+    await expectError(
+      setFeeReceivers([
+        { receiver, bps: 5_000 },
+        { receiver, bps: 5_000 },
+      ]),
+      "InvalidFeeReceivers"
+    );
+  });
+
+  it("splits a sweep by bps, the remainder going to the last receiver", async () => {
+    const first = await fundedKeypair(1);
+    const last = await fundedKeypair(1);
+    await setFeeReceivers([
+      { receiver: first.publicKey, bps: 3_000 },
+      { receiver: last.publicKey, bps: 7_000 },
+    ]);
+
+    await expectError(sweep(new BN(1_001)), "FeeReceiverMismatch");
+    await sweep(new BN(1_001), receivers(first.publicKey, last.publicKey));
+
+    expect(await connection.getBalance(first.publicKey, "confirmed")).to.equal(LAMPORTS_PER_SOL + 300);
+    expect(await connection.getBalance(last.publicKey, "confirmed")).to.equal(LAMPORTS_PER_SOL + 701);
+  });
+});
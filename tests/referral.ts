@@ -0,0 +1,82 @@
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import { buy, eventCpi, expectError, fundedKeypair, launchToken2022, pda, program, setup } from "./helpers";
+
+/// Bind `user` to `referrer`, passing the bindings of the referrer's `upline`.
+function bind(user: Keypair, referrer: PublicKey, upline: PublicKey[] = []) {
+  return program.methods
+    .bindReferrer()
+    .accounts({
+      referralBinding: pda("referral_binding", user.publicKey),
+      referrer,
+      referrerFeeRecommendReward: pda("fee_recommend_reward", referrer),
+      user: user.publicKey,
+      systemProgram: SystemProgram.programId,
+      ...eventCpi,
+    })
+    .remainingAccounts(
+      [referrer, ...upline].map((key) => ({
+        pubkey: pda("referral_binding", key),
+        isSigner: false,
+        isWritable: false,
+      }))
+    )
+    .signers([user])
+    .rpc({ commitment: "confirmed" });
+}
+
+describe("bind_referrer", () => {
+  let referrer: Keypair;
+  let user: Keypair;
+
+  before(async () => {
+    await setup();
+    referrer = await fundedKeypair();
+    user = await fundedKeypair();
+  });
+
+  it("binds a user to their referrer once", async () => {
+    await bind(user, referrer.publicKey);
+
+    const binding = await program.account.referralBinding.fetch(pda("referral_binding", user.publicKey));
+    expect(binding.referrer.equals(referrer.publicKey)).to.be.true;
+    expect(await program.account.feeRecommendReward.fetchNullable(pda("fee_recommend_reward", referrer.publicKey)))
+      .to.not.be.null;
+  });
+
+  it("rejects self-referrals and cycles", async () => {
+    const stranger = await fundedKeypair();
+    await expectError(bind(stranger, stranger.publicKey), "InvalidReferrer");
+
+    await expectError(bind(referrer, user.publicKey, [referrer.publicKey]), "InvalidReferrer");
+  });
+
+  it("requires the referrer's upline", async () => {
+    const newcomer = await fundedKeypair();
+    await expectError(
+      program.methods
+        .bindReferrer()
+        .accounts({
+          referralBinding: pda("referral_binding", newcomer.publicKey),
+          referrer: user.publicKey,
+          referrerFeeRecommendReward: pda("fee_recommend_reward", user.publicKey),
+          user: newcomer.publicKey,
+          systemProgram: SystemProgram.programId,
+          ...eventCpi,
+        })
+        .signers([newcomer])
+        .rpc({ commitment: "confirmed" }),
+      "ReferralChainMismatch"
+    );
+  });
+
+  it("pays the bound referrer on trades and rejects a truncated chain", async () => {
+    const launch = await launchToken2022(await fundedKeypair());
+
+    await expectError(buy(user, launch, LAMPORTS_PER_SOL), "ReferralChainMismatch");
+
+    await buy(user, launch, LAMPORTS_PER_SOL, { upline: [referrer.publicKey] });
+    const reward = await program.account.feeRecommendReward.fetch(pda("fee_recommend_reward", referrer.publicKey));
+    expect(reward.unclaimedSol.toNumber()).to.be.greaterThan(0);
+  });
+});
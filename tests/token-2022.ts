@@ -0,0 +1,150 @@
+import { BN } from "@coral-xyz/anchor";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotent,
+  getAccount,
+  getMint,
+  getTransferFeeAmount,
+  getTransferFeeConfig,
+} from "@solana/spl-token";
+import { Keypair, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  Launch,
+  NOT_ON_CURVE,
+  admin,
+  ata,
+  buy,
+  connection,
+  eventCpi,
+  expectError,
+  feeConfig,
+  feeReceiver,
+  fundedKeypair,
+  launchToken2022,
+  pda,
+  program,
+  programSigner,
+  programSystemAccount,
+  protocolFeeVault,
+  setup,
+  tokenBalance,
+} from "./helpers";
+
+const transferFee = { transferFeeBasisPoints: 100, maximumTransferFee: new BN("1000000000000000") };
+
+describe("create_token_2022", () => {
+  before(setup);
+
+  it("launches a Token-2022 mint with a transfer fee", async () => {
+    const creator = await fundedKeypair();
+
+    const launch = await launchToken2022(creator, {}, transferFee);
+
+    const mint = await getMint(connection, launch.mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(getTransferFeeConfig(mint).newerTransferFee.transferFeeBasisPoints).to.equal(100);
+    expect(await tokenBalance(ata(launch.mint, programSigner)) > 0n).to.be.true;
+
+    const curveConfig = await program.account.curveConfig.fetch(launch.curveConfig);
+    expect(curveConfig.creator.equals(creator.publicKey)).to.be.true;
+    const profile = await program.account.creatorProfile.fetch(pda("creator_profile", creator.publicKey));
+    expect(profile.tokensLaunched.toNumber()).to.equal(1);
+  });
+
+  it("rejects a creator allocation larger than the tokens kept off the curve", async () => {
+    const creator = await fundedKeypair();
+
+    await expectError(
+      launchToken2022(creator, { creatorAllocation: NOT_ON_CURVE.addn(1), vestingDuration: new BN(60) }),
+      "CreatorAllocationTooLarge"
+    );
+  });
+});
+
+describe("withdraw_transfer_fees", () => {
+  let launch: Launch;
+  let trader: Keypair;
+
+  before(async () => {
+    await setup();
+    launch = await launchToken2022(await fundedKeypair(), {}, transferFee);
+    trader = await fundedKeypair();
+    await buy(trader, launch, LAMPORTS_PER_SOL);
+  });
+
+  function withdraw(source = ata(launch.mint, trader.publicKey), writable = true, signer = admin) {
+    return program.methods
+      .withdrawTransferFees()
+      .accounts({
+        programSystemAccount,
+        protocolFeeVault,
+        programSigner,
+        tokenMint: launch.mint,
+        protocolFeeVaultAta: ata(launch.mint, protocolFeeVault),
+        admin: signer.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        ...eventCpi,
+      })
+      .remainingAccounts([{ pubkey: source, isSigner: false, isWritable: writable }])
+      .signers([signer])
+      .rpc({ commitment: "confirmed" });
+  }
+
+  function sweep(receiverAta = ata(launch.mint, feeReceiver.publicKey)) {
+    return program.methods
+      .sweepProtocolTokenFees(null)
+      .accounts({
+        programSystemAccount,
+        protocolFeeVault,
+        protocolFeeVaultAta: ata(launch.mint, protocolFeeVault),
+        tokenMint: launch.mint,
+        feeConfig,
+        admin: admin.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        ...eventCpi,
+      })
+      .remainingAccounts([{ pubkey: receiverAta, isSigner: false, isWritable: true }])
+      .rpc({ commitment: "confirmed" });
+  }
+
+  it("is admin only", async () => {
+    await expectError(withdraw(undefined, true, await fundedKeypair()), "ConstraintRaw");
+  });
+
+  it("rejects read-only sources", async () => {
+    await expectError(withdraw(undefined, false), "InvalidTransferFeeSource");
+  });
+
+  it("moves the withheld fees to the protocol fee vault", async () => {
+    const traderAta = ata(launch.mint, trader.publicKey);
+    const account = await getAccount(connection, traderAta, "confirmed", TOKEN_2022_PROGRAM_ID);
+    const withheld = getTransferFeeAmount(account).withheldAmount;
+    expect(withheld > 0n).to.be.true;
+
+    await withdraw();
+
+    expect(await tokenBalance(ata(launch.mint, protocolFeeVault))).to.equal(withheld);
+    const after = await getAccount(connection, traderAta, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(getTransferFeeAmount(after).withheldAmount).to.equal(0n);
+  });
+
+  it("sweeps the vault's tokens to the fee receivers", async () => {
+    await expectError(sweep(ata(launch.mint, trader.publicKey)), "FeeReceiverMismatch");
+
+    const receiverAta = await createAssociatedTokenAccountIdempotent(
+      connection,
+      admin,
+      launch.mint,
+      feeReceiver.publicKey,
+      { commitment: "confirmed" },
+      TOKEN_2022_PROGRAM_ID
+    );
+    await sweep();
+
+    expect(await tokenBalance(ata(launch.mint, protocolFeeVault))).to.equal(0n);
+    expect(await tokenBalance(receiverAta) > 0n).to.be.true;
+  });
+});
@@ -0,0 +1,84 @@
+import { BN } from "@coral-xyz/anchor";
+import { ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  Launch,
+  ata,
+  eventCpi,
+  expectError,
+  fundedKeypair,
+  launchToken2022,
+  pda,
+  program,
+  setup,
+  tokenBalance,
+  waitForClock,
+} from "./helpers";
+
+const allocation = new BN("1000000000000000");
+
+describe("claim_vested", () => {
+  let creator: Keypair;
+  let launch: Launch;
+
+  before(async () => {
+    await setup();
+    creator = await fundedKeypair();
+    launch = await launchToken2022(creator, {
+      creatorAllocation: allocation,
+      vestingCliff: new BN(2),
+      vestingDuration: new BN(6),
+    });
+  });
+
+  function claim(signer = creator) {
+    const creatorVesting = pda("creator_vesting", launch.mint);
+    return program.methods
+      .claimVested()
+      .accounts({
+        creatorVesting,
+        vestingVault: ata(launch.mint, creatorVesting),
+        creatorAta: ata(launch.mint, signer.publicKey),
+        tokenMint: launch.mint,
+        creator: signer.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        ...eventCpi,
+      })
+      .signers([signer])
+      .rpc({ commitment: "confirmed" });
+  }
+
+  it("locks the allocation in the vesting vault", async () => {
+    const creatorVesting = pda("creator_vesting", launch.mint);
+    expect(await tokenBalance(ata(launch.mint, creatorVesting))).to.equal(BigInt(allocation.toString()));
+  });
+
+  it("has nothing to claim before the cliff", async () => {
+    await expectError(claim(), "NothingToClaim");
+  });
+
+  it("is creator only", async () => {
+    await expectError(claim(await fundedKeypair()), "ConstraintHasOne");
+  });
+
+  it("releases the allocation linearly after the cliff", async () => {
+    const vesting = await program.account.creatorVesting.fetch(pda("creator_vesting", launch.mint));
+    const creatorAta = ata(launch.mint, creator.publicKey);
+
+    await waitForClock(vesting.cliffAt);
+    await claim();
+    const vested = await tokenBalance(creatorAta);
+    expect(vested > 0n).to.be.true;
+
+    await waitForClock(vesting.endAt);
+    if (vested < BigInt(allocation.toString())) {
+      await claim();
+    }
+    expect(await tokenBalance(creatorAta)).to.equal(BigInt(allocation.toString()));
+
+    await expectError(claim(), "NothingToClaim");
+  });
+});